@@ -0,0 +1,294 @@
+//! Terminal client: plays the game without the desktop app, sharing its saves
+
+use clap::Parser;
+use luup_core::agent::AgentMessage;
+use luup_core::cassette::Cassette;
+use luup_core::character::CharacterSheet;
+use luup_core::logging::{self, LogConfig};
+use luup_core::ollama::ErrorKind;
+use luup_core::scenario::Scenario;
+use luup_core::state::GameState;
+use luup_core::{Engine, EngineConfig, Game};
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// The desktop app's identifier; its data directory is named after it
+const APP_IDENTIFIER: &str = "com.luup.app";
+
+const DIM: &str = "\x1b[2m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+const HELP: &str = "Type an action, or the number of a choice.
+  /reasoning  show or hide reasoning, rulings and tool calls
+  /state      show the current game state
+  /quit       leave (the game is saved after every turn)";
+
+#[derive(Parser)]
+#[command(name = "luup-cli", about = "Play Luup in the terminal")]
+struct Args {
+    /// Scenario to start; asked for when omitted
+    #[arg(long)]
+    scenario: Option<String>,
+    /// Id of a save to resume instead of starting a new game
+    #[arg(long)]
+    load: Option<String>,
+    /// List saved games and exit
+    #[arg(long)]
+    list_saves: bool,
+    /// Ollama host and port, e.g. 127.0.0.1:11434
+    #[arg(long)]
+    ollama: Option<String>,
+    /// Show the model's reasoning, the arbiter's rulings and tool calls
+    #[arg(long)]
+    reasoning: bool,
+    /// App data directory with saves, scenarios and overrides; defaults to the desktop app's
+    #[arg(long)]
+    data_dir: Option<PathBuf>,
+    /// Directory of bundled scenario packs; defaults to the source tree's
+    #[arg(long)]
+    scenarios: Option<PathBuf>,
+    /// Record every request to Ollama and its raw reply to this cassette file
+    #[arg(long, conflicts_with = "replay")]
+    record: Option<PathBuf>,
+    /// Replay a recorded cassette instead of calling Ollama; type the same actions to re-run it
+    #[arg(long)]
+    replay: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Args::parse()).await {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: Args) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let data_dir = args
+        .data_dir
+        .clone()
+        .or_else(|| dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER)))
+        .ok_or("Can't find the app data directory; pass --data-dir")?;
+    // The terminal is the game's, so diagnostics only go to the log file
+    let _log_guard = logging::init(LogConfig {
+        log_dir: Some(data_dir.join("logs")),
+        file_name: "luup-cli",
+        stderr: false,
+        default_filter: "info",
+    })?;
+    let cassette = match (&args.record, &args.replay) {
+        (Some(path), _) => Some(Cassette::record(path)),
+        (_, Some(path)) => Some(Cassette::replay(path)?),
+        _ => None,
+    };
+    // Bundled packs, then the player's own
+    let bundled = args
+        .scenarios
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../src-tauri/scenarios")));
+    let engine = Engine::new(EngineConfig {
+        scenario_roots: vec![bundled, data_dir.join("scenarios")],
+        data_dir: Some(data_dir),
+        ollama_url: args.ollama.clone(),
+        model: None,
+        cassette,
+        dice_seed: None,
+    });
+
+    if args.list_saves {
+        for save in engine.list_saves().await {
+            println!("{}  {} ({} turns, {})", save.id, save.name, save.turn_count, save.last_played);
+        }
+        return Ok(());
+    }
+
+    let mut game = match &args.load {
+        Some(save_id) => engine.load_game(save_id).await?,
+        None => {
+            let game = new_game(&engine, args.scenario.as_deref()).await?;
+            engine.save(&game).await?;
+            game
+        }
+    };
+
+    println!("{}{}{}  (/help for commands)\n", BOLD, game.name(), RESET);
+    if let Some(turn) = game.current_turn() {
+        println!("{}", turn.story_text);
+        print_choices(&turn.choices);
+    }
+
+    let mut show_reasoning = args.reasoning;
+    while let Some(line) = prompt("\n> ")? {
+        let choices = game.current_turn().map(|t| t.choices.clone()).unwrap_or_default();
+        let action = match line.as_str() {
+            "" => continue,
+            "/quit" | "/q" => break,
+            "/help" => {
+                println!("{}", HELP);
+                continue;
+            }
+            "/reasoning" => {
+                show_reasoning = !show_reasoning;
+                println!("Reasoning {}", if show_reasoning { "shown" } else { "hidden" });
+                continue;
+            }
+            "/state" => {
+                print_state(game.state());
+                continue;
+            }
+            other => match other.parse::<usize>() {
+                Ok(n) if (1..=choices.len()).contains(&n) => choices[n - 1].clone(),
+                _ => other.to_string(),
+            },
+        };
+
+        println!();
+        if play_turn(&mut game, action, show_reasoning).await {
+            engine.save(&game).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn new_game(engine: &Engine, scenario_id: Option<&str>) -> Result<Game, Box<dyn Error + Send + Sync>> {
+    let scenarios = engine.scenarios().await;
+    let scenario = match scenario_id {
+        Some(id) => scenarios
+            .iter()
+            .find(|s| s.id() == id)
+            .ok_or_else(|| format!("Scenario '{}' not found", id))?,
+        None => pick_scenario(&scenarios)?,
+    };
+    let character = create_character(scenario)?;
+    engine.new_game(Some(scenario.id()), Some(character)).await
+}
+
+fn pick_scenario(scenarios: &[Scenario]) -> Result<&Scenario, Box<dyn Error + Send + Sync>> {
+    if scenarios.is_empty() {
+        return Err("No scenario packs found".into());
+    }
+    for (i, scenario) in scenarios.iter().enumerate() {
+        println!("{}. {}{}{} - {}", i + 1, BOLD, scenario.manifest.name, RESET, scenario.manifest.description);
+    }
+    loop {
+        let line = prompt("Scenario: ")?.ok_or("No scenario chosen")?;
+        match line.parse::<usize>() {
+            Ok(n) if (1..=scenarios.len()).contains(&n) => return Ok(&scenarios[n - 1]),
+            _ => println!("Pick a number from 1 to {}", scenarios.len()),
+        }
+    }
+}
+
+/// Ask for a name; everything else comes from the scenario's default character
+fn create_character(scenario: &Scenario) -> Result<CharacterSheet, Box<dyn Error + Send + Sync>> {
+    let template = &scenario.manifest.character;
+    let default = template.default_sheet();
+    let name = prompt(&format!("Your name [{}]: ", default.name))?.unwrap_or_default();
+    if name.is_empty() {
+        return Ok(default);
+    }
+    Ok(template.finalize(CharacterSheet { name, ..default })?)
+}
+
+/// Stream one turn to the terminal. The game only changes if the turn completes.
+async fn play_turn(game: &mut Game, action: String, show_reasoning: bool) -> bool {
+    // What is being printed, so each switch starts on a fresh line
+    let mut section = String::new();
+    let mut switch_to = |name: &str| {
+        if section != name {
+            if !section.is_empty() {
+                println!("\n");
+            }
+            section = name.to_string();
+            true
+        } else {
+            false
+        }
+    };
+
+    let result = game
+        .take_action(action, |message| {
+            match message {
+                AgentMessage::TextChunk { content } => {
+                    switch_to("story");
+                    print!("{}", content);
+                }
+                AgentMessage::Dialogue { speaker, content } => {
+                    if switch_to(&speaker) {
+                        print!("{}{}:{} ", BOLD, speaker, RESET);
+                    }
+                    print!("{}", content);
+                }
+                AgentMessage::ReasoningChunk { content } if show_reasoning => {
+                    switch_to("reasoning");
+                    print!("{}{}{}", DIM, content, RESET);
+                }
+                AgentMessage::RulingChunk { content } if show_reasoning => {
+                    switch_to("ruling");
+                    print!("{}{}{}", DIM, content, RESET);
+                }
+                AgentMessage::StageStarted { stage } if show_reasoning => {
+                    switch_to("stage");
+                    print!("{}[{:?}]{}", DIM, stage, RESET);
+                }
+                AgentMessage::ToolCall { name, args } if show_reasoning => {
+                    switch_to("tool");
+                    print!("{}[{} {}]{}", DIM, name, args, RESET);
+                }
+                // Rejected tool calls go back to the model, which usually recovers
+                AgentMessage::Error { message, details, .. } if details.is_none() || show_reasoning => {
+                    switch_to("error");
+                    eprint!("{}", message);
+                }
+                _ => {}
+            }
+            let _ = io::stdout().flush();
+        })
+        .await;
+    println!();
+
+    match result {
+        Ok(turn) => {
+            print_choices(&turn.choices);
+            true
+        }
+        Err(e) => {
+            // Errors from Ollama were already shown as the turn streamed
+            if ErrorKind::of(&*e).is_none() {
+                eprintln!("The storyteller failed: {}", e);
+            }
+            false
+        }
+    }
+}
+
+fn print_choices(choices: &[String]) {
+    println!();
+    for (i, choice) in choices.iter().enumerate() {
+        println!("{}{}.{} {}", BOLD, i + 1, RESET, choice);
+    }
+}
+
+fn print_state(state: &GameState) {
+    println!("{} at {}, wearing {}", state.clock, state.location, state.outfit);
+    let exits = state.world.neighbours(&state.location);
+    if !exits.is_empty() {
+        println!("Exits: {}", exits.join(", "));
+    }
+    if !state.variables.is_empty() {
+        println!("{}", state.variables.render());
+    }
+}
+
+/// Read a trimmed line; `None` at end of input
+fn prompt(text: &str) -> io::Result<Option<String>> {
+    print!("{}", text);
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if io::stdin().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
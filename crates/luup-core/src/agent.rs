@@ -0,0 +1,904 @@
+use crate::cassette::Cassette;
+use crate::character::CharacterSheet;
+use crate::context::{estimate_messages, estimate_tokens, ContextBudget};
+use crate::logging::redact;
+use crate::lorebook::{InsertionPosition, LoreEntry, Lorebook};
+use crate::memory::{chunk_turn, MemoryStore};
+use crate::npc::{tool_definition, NpcAgent, NpcMemory, TalkToNpcArgs, TALK_TO_NPC};
+use crate::ollama::{
+    ChatMessage, ErrorKind, ModelOptions, OllamaClient, RequestPolicy, StreamChunk, Tool, DEFAULT_URL,
+};
+use crate::pipeline::{PipelineConfig, PipelineStage};
+use crate::prompts::{
+    DueEvent, NpcPromptContext, PromptSettings, PromptTemplates, RecalledMemory, RulingChange,
+    RulingPromptContext, SummaryPromptContext, SystemPromptContext, ToolInfo, TranscriptEntry, UserPromptContext,
+};
+use crate::scenario::Scenario;
+use crate::scripting::{dice, load_script_tools, Dice};
+use crate::state::{GameState, TurnData};
+use crate::tools::{validate_arguments, DynTool, ToolError, ToolRegistry};
+use crate::usage::Usage;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+use std::time::Instant;
+use tokio_stream::StreamExt;
+use tracing::{debug, error, info, trace, warn};
+use ts_rs::TS;
+
+/// Maximum number of model responses per turn when tools keep being called
+const MAX_TOOL_ROUNDS: usize = 4;
+
+/// Opening of the system prompt for scenarios that don't provide a persona
+const DEFAULT_PERSONA: &str = "You are a creative and immersive dungeon master for a text-based adventure game.";
+
+/// Number of past turns handed to the user prompt template
+const RECENT_TURNS: usize = 5;
+
+/// Memories recalled into the prompt for each action
+const RECALLED_MEMORIES: usize = 3;
+
+/// Choices offered when none can be extracted from the story
+pub const DEFAULT_CHOICES: [&str; 3] = [
+    "Continue exploring",
+    "Examine your surroundings carefully",
+    "Take a different approach",
+];
+
+/// Messages that can be streamed to the frontend
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export)]
+pub enum AgentMessage {
+    TextChunk { content: String },
+    ReasoningChunk { content: String },
+    /// A pipeline stage began; everything until the next one belongs to it
+    StageStarted { stage: PipelineStage },
+    /// The arbiter's ruling, streamed before the narration
+    RulingChunk { content: String },
+    /// A line spoken by an NPC's own agent
+    Dialogue { speaker: String, content: String },
+    ToolCall { name: String, args: Value },
+    ToolResult { name: String, result: GameState },
+    Choices { choices: Vec<String> },
+    /// Tokens and timings of the turn, sent just before it completes
+    Usage(Usage),
+    TurnComplete(TurnData),
+    Error {
+        message: String,
+        /// Structured context, e.g. the offending tool-call arguments
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        details: Option<Value>,
+        /// Set when talking to Ollama failed
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        kind: Option<ErrorKind>,
+    },
+}
+
+/// The agent's part of a saved session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentSession {
+    pub conversation_history: Vec<ChatMessage>,
+    pub story_summary: String,
+    pub memory: MemoryStore,
+    /// What each NPC remembers of talking with the player, by name
+    pub npcs: BTreeMap<String, NpcMemory>,
+}
+
+/// What one model pass of a turn produced
+#[derive(Default)]
+struct StageOutput {
+    text: String,
+    /// Assistant and tool messages to add to the conversation
+    messages: Vec<ChatMessage>,
+    /// Tool calls made, with their results
+    changes: Vec<RulingChange>,
+    /// NPC conversations as (NPC, what they were told, their reply)
+    conversations: Vec<(String, String, String)>,
+    usage: Usage,
+    /// When the first chunk of any kind arrived
+    first_chunk_at: Option<Instant>,
+}
+
+/// The agentic system that manages the game loop
+#[derive(Clone)]
+pub struct Agent {
+    client: OllamaClient,
+    conversation_history: Vec<ChatMessage>,
+    /// Rolling summary of the turns folded out of `conversation_history`
+    story_summary: String,
+    /// Embedded past turns, recalled when relevant to the player's action
+    memory: MemoryStore,
+    budget: ContextBudget,
+    tools: ToolRegistry,
+    prompts: PromptTemplates,
+    lorebook: Lorebook,
+    /// Arbiter and narrator settings when each turn is split into two passes
+    pipeline: Option<PipelineConfig>,
+    arbiter_prompt: String,
+    npcs: Vec<NpcAgent>,
+    npc_memory: BTreeMap<String, NpcMemory>,
+    /// Random source for the scripted tools' dice rolls
+    dice: Dice,
+}
+
+impl Default for Agent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Agent {
+    pub fn new() -> Self {
+        Self::with_ollama_url(DEFAULT_URL.to_string())
+    }
+
+    pub fn with_ollama_url(base_url: String) -> Self {
+        Self {
+            client: OllamaClient::with_url(base_url),
+            conversation_history: Vec::new(),
+            story_summary: String::new(),
+            memory: MemoryStore::default(),
+            budget: ContextBudget::default(),
+            tools: ToolRegistry::builtin(),
+            prompts: PromptTemplates::default(),
+            lorebook: Lorebook::default(),
+            pipeline: None,
+            arbiter_prompt: String::new(),
+            npcs: Vec::new(),
+            npc_memory: BTreeMap::new(),
+            dice: dice(None),
+        }
+    }
+
+    pub fn model(&self) -> &str {
+        self.client.model()
+    }
+
+    /// The game tools this agent's model may call
+    pub fn tools(&self) -> &ToolRegistry {
+        &self.tools
+    }
+
+    /// Use another storytelling model; set it before starting a game, as NPCs
+    /// and the context budget follow it
+    pub fn set_model(&mut self, model: &str) {
+        self.client = self.client.with_model(Some(model), &ModelOptions::default());
+    }
+
+    /// Record the model traffic to a cassette, or replay one instead of calling Ollama.
+    /// Set it before starting a game so the scenario's NPCs share it.
+    pub fn set_cassette(&mut self, cassette: Option<Cassette>) {
+        self.client.set_cassette(cassette);
+    }
+
+    /// Timeouts and retries for the model requests; set it before starting a game
+    /// so the scenario's NPCs share it
+    pub fn set_request_policy(&mut self, policy: RequestPolicy) {
+        self.client.set_request_policy(policy);
+    }
+
+    /// Set how much context the conversation may use before old turns are summarized
+    pub fn set_context_budget(&mut self, budget: ContextBudget) {
+        self.client.set_context_length(budget.context_tokens);
+        self.budget = budget;
+    }
+
+    /// Limit the tools offered to the model; `None` enables every registered tool
+    pub fn set_enabled_tools(&mut self, enabled: Option<&[String]>) {
+        self.tools.set_enabled(enabled);
+    }
+
+    /// Split turns into an arbiter pass and a narrator pass; takes effect when
+    /// the next game starts, as the system prompt depends on it
+    pub fn set_pipeline(&mut self, config: PipelineConfig) {
+        self.pipeline = config.enabled.then_some(config);
+    }
+
+    /// Make the scripted tools' dice rolls repeatable; set it before starting a game
+    pub fn set_dice_seed(&mut self, seed: u64) {
+        self.dice = dice(Some(seed));
+    }
+
+    /// Register the `*.rhai` tool scripts in `dir` alongside the built-in tools. Scripts named
+    /// like a tool that is already registered are skipped. Returns how many were added.
+    pub fn load_script_tools(&mut self, dir: &Path) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let mut count = 0;
+        for tool in load_script_tools(dir, &self.dice)? {
+            let name = tool.name().to_string();
+            if name != TALK_TO_NPC && self.tools.register_new(tool) {
+                count += 1;
+            } else {
+                warn!(tool = %name, "A script tool has the name of a built-in tool; keeping the built-in");
+            }
+        }
+        Ok(count)
+    }
+
+    /// Initialize a new game session from a scenario pack for the given player
+    /// character, rendering prompts with `prompts`
+    pub fn start_new_game(
+        &mut self,
+        scenario: &Scenario,
+        prompts: PromptTemplates,
+        character: CharacterSheet,
+    ) -> Result<GameState, Box<dyn Error + Send + Sync>> {
+        self.conversation_history.clear();
+        self.story_summary.clear();
+        self.memory = MemoryStore::default();
+        self.npc_memory.clear();
+        self.prompts = prompts;
+        self.lorebook = scenario.lorebook.clone();
+
+        // Each scenario brings its own scripted tools and tool selection
+        self.tools = ToolRegistry::builtin();
+        let tools_dir = scenario.tools_dir();
+        if tools_dir.is_dir() {
+            let count = self.load_script_tools(&tools_dir)?;
+            info!(count, scenario = scenario.id(), "Loaded script tools");
+        }
+        self.set_enabled_tools(scenario.manifest.enabled_tools.as_deref());
+
+        // Add system prompt
+        let mut initial_state = scenario.initial_state();
+        if !character.outfit.is_empty() {
+            initial_state.outfit = character.outfit.clone();
+        }
+        initial_state.character = character;
+
+        // NPCs speak through talk_to_npc, so they only exist where that tool is allowed
+        self.npcs.clear();
+        let npcs_enabled = scenario
+            .manifest
+            .enabled_tools
+            .as_ref()
+            .is_none_or(|tools| tools.iter().any(|t| t == TALK_TO_NPC));
+        if npcs_enabled {
+            for npc in &scenario.npcs {
+                let system_prompt = self.prompts.render_npc(&NpcPromptContext {
+                    name: &npc.def.name,
+                    persona: npc.persona.trim(),
+                    lore: scenario.lore.as_deref().map(str::trim),
+                    scenario: scenario.summary(),
+                    character: &initial_state.character,
+                    settings: self.prompt_settings(),
+                })?;
+                let client = self.client.with_model(npc.def.model.as_deref(), &npc.def.options);
+                self.npcs.push(NpcAgent::new(npc.def.name.clone(), system_prompt, client));
+            }
+        }
+
+        let system_prompt = self.create_system_prompt(scenario, &initial_state)?;
+        self.conversation_history.push(ChatMessage::new("system", system_prompt));
+        self.arbiter_prompt = match self.pipeline {
+            Some(_) => self.prompts.render_arbiter(&self.system_prompt_context(scenario, &initial_state))?,
+            None => String::new(),
+        };
+
+        Ok(initial_state)
+    }
+
+    /// Snapshot of the conversation and memory for saving
+    pub fn session(&self) -> AgentSession {
+        AgentSession {
+            conversation_history: self.conversation_history.clone(),
+            story_summary: self.story_summary.clone(),
+            memory: self.memory.clone(),
+            npcs: self.npc_memory.clone(),
+        }
+    }
+
+    /// Resume a saved session; call after `start_new_game` has set up the scenario.
+    /// The freshly rendered system prompt replaces the saved one, so template and
+    /// pipeline changes apply to old saves too.
+    pub fn restore_session(&mut self, session: AgentSession) {
+        let system_prompt = self.conversation_history.first().cloned();
+        self.conversation_history = session.conversation_history;
+        if let (Some(prompt), Some(first)) = (system_prompt, self.conversation_history.first_mut()) {
+            if first.role == "system" {
+                *first = prompt;
+            }
+        }
+        self.story_summary = session.story_summary;
+        self.memory = session.memory;
+        self.npc_memory = session.npcs;
+    }
+
+    /// Main agentic loop - processes an action and streams responses
+    pub async fn process_action<F>(
+        &mut self,
+        action: String,
+        current_state: &mut GameState,
+        history: &[TurnData],
+        mut emit: F,
+    ) -> Result<(), Box<dyn Error + Send + Sync>>
+    where
+        F: FnMut(AgentMessage) + Send,
+    {
+        let turn_number = history.len() as u32;
+        let started = Instant::now();
+        let mut usage = Usage {
+            context_window: self.budget.context_tokens as u32,
+            ..Usage::default()
+        };
+        let first_chunk_at;
+
+        // Add user action to conversation
+        let memories = self.recall(&action, turn_number).await;
+        let world_info = self.select_world_info(&action, history);
+        let user_message = self.format_user_message(&action, current_state, history, memories, &world_info)?;
+        // Events have been reported to the model; new ones may fire during this turn
+        current_state.triggered_events.clear();
+        self.conversation_history.push(ChatMessage::new("user", user_message));
+
+        // Get tools
+        let tools = self.tool_definitions();
+        self.compact_history(&tools, &world_info).await;
+
+        let accumulated_text = match self.pipeline.clone() {
+            None => {
+                let output = self
+                    .run_stage(&self.client, self.request_messages(&world_info), tools, None, current_state, &mut emit)
+                    .await?;
+                usage.add(&output.usage);
+                first_chunk_at = output.first_chunk_at;
+                self.remember_conversations(&output);
+                self.conversation_history.extend(output.messages);
+                output.text
+            }
+            Some(pipeline) => {
+                // The arbiter sees the same conversation under its own system prompt
+                let arbiter = self.client.with_model(pipeline.arbiter.model.as_deref(), &pipeline.arbiter.options);
+                let mut request = self.request_messages(&world_info);
+                if let Some(system) = request.first_mut() {
+                    *system = ChatMessage::new("system", self.arbiter_prompt.clone());
+                }
+                let ruling = self
+                    .run_stage(&arbiter, request, tools, Some(PipelineStage::Arbiter), current_state, &mut emit)
+                    .await?;
+                usage.add(&ruling.usage);
+                let ruling_first_chunk_at = ruling.first_chunk_at;
+                self.remember_conversations(&ruling);
+                // Only the ruling is kept; the arbiter's tool chatter would distract the narrator
+                let ruling = self.format_ruling(&ruling, current_state)?;
+                self.conversation_history.push(ChatMessage::new("system", ruling));
+
+                let narrator = self.client.with_model(pipeline.narrator.model.as_deref(), &pipeline.narrator.options);
+                let output = self
+                    .run_stage(
+                        &narrator,
+                        self.request_messages(&world_info),
+                        Vec::new(),
+                        Some(PipelineStage::Narrator),
+                        current_state,
+                        &mut emit,
+                    )
+                    .await?;
+                usage.add(&output.usage);
+                first_chunk_at = ruling_first_chunk_at.or(output.first_chunk_at);
+                self.conversation_history.extend(output.messages);
+                output.text
+            }
+        };
+        usage.first_token_ms = first_chunk_at.map(|at| at.duration_since(started).as_millis() as u32);
+        debug!(chars = accumulated_text.len(), "Accumulated story text");
+
+        // Generate choices (for now, use defaults - could be extracted from model response)
+        let choices = self.extract_choices(&accumulated_text);
+        debug!(count = choices.len(), "Extracted choices");
+
+        // Emit turn complete
+        info!(
+            chars = accumulated_text.len(),
+            choices = choices.len(),
+            prompt_tokens = usage.prompt_tokens,
+            completion_tokens = usage.completion_tokens,
+            "Turn complete"
+        );
+        emit(AgentMessage::Usage(usage.clone()));
+        emit(AgentMessage::TurnComplete(TurnData {
+            turn_number,
+            story_text: accumulated_text.clone(),
+            choices: choices.clone(),
+            game_state: current_state.clone(),
+            usage: Some(usage),
+        }));
+
+        self.remember(turn_number, &action, &accumulated_text).await;
+
+        Ok(())
+    }
+
+    /// Stream one model's reply to `messages`, executing its tool calls. Tool results
+    /// are fed back so the model can read them, until it answers without calling any.
+    async fn run_stage<F>(
+        &self,
+        client: &OllamaClient,
+        mut messages: Vec<ChatMessage>,
+        tools: Vec<Tool>,
+        stage: Option<PipelineStage>,
+        current_state: &mut GameState,
+        emit: &mut F,
+    ) -> Result<StageOutput, Box<dyn Error + Send + Sync>>
+    where
+        F: FnMut(AgentMessage) + Send,
+    {
+        if let Some(stage) = stage {
+            info!(?stage, model = client.model(), "Starting stage");
+            emit(AgentMessage::StageStarted { stage });
+        }
+
+        let mut output = StageOutput::default();
+
+        // Each round streams one model response
+        for round in 0..MAX_TOOL_ROUNDS {
+            // Call Ollama with streaming; a failure here leaves the turn unplayed
+            let mut stream = match client.chat_stream(messages.clone(), tools.clone()).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    error!(error = %e, "Ollama request failed");
+                    emit(AgentMessage::Error {
+                        message: e.to_string(),
+                        details: None,
+                        kind: ErrorKind::of(&*e),
+                    });
+                    return Err(e);
+                }
+            };
+
+            let mut round_text = String::new();
+            let mut tool_calls = Vec::new();
+            let mut tool_results = Vec::new();
+            let mut stream_failed = false;
+
+            // Process stream
+            debug!(round = round + 1, "Processing Ollama stream");
+            while let Some(chunk_result) = stream.next().await {
+                output.first_chunk_at.get_or_insert_with(Instant::now);
+                match chunk_result {
+                    Ok(chunk) => match chunk {
+                        StreamChunk::TextChunk(content) => {
+                            trace!(content = %redact(&content), "Text chunk");
+                            round_text.push_str(&content);
+                            // The arbiter's text is a ruling for the narrator, not story
+                            emit(if stage == Some(PipelineStage::Arbiter) {
+                                AgentMessage::RulingChunk { content }
+                            } else {
+                                AgentMessage::TextChunk { content }
+                            });
+                        }
+                        StreamChunk::ReasoningChunk(content) => {
+                            trace!(content = %redact(&content), "Reasoning chunk");
+                            emit(AgentMessage::ReasoningChunk { content });
+                        }
+                        StreamChunk::ToolCall { name, arguments } => {
+                            info!(tool = %name, arguments = %redact(&arguments.to_string()), "Tool call");
+                            // Emit tool call notification
+                            emit(AgentMessage::ToolCall {
+                                name: name.clone(),
+                                args: arguments.clone(),
+                            });
+
+                            // Execute tool and update state; NPCs answer through their own agents
+                            let result = if name == TALK_TO_NPC && !self.npcs.is_empty() {
+                                self.talk_to_npc(&arguments, current_state, emit).await.map(|(npc, said, reply)| {
+                                    let tool_output =
+                                        format!("{} says: \"{}\" Quote these words in the story.", npc, reply);
+                                    output.conversations.push((npc, said, reply));
+                                    tool_output
+                                })
+                            } else {
+                                self.tools.execute(&name, &arguments, current_state)
+                            };
+                            let tool_output = match result {
+                                Ok(tool_output) => {
+                                    debug!(tool = %name, "Tool executed");
+                                    // Emit updated state
+                                    emit(AgentMessage::ToolResult {
+                                        name: name.clone(),
+                                        result: current_state.clone(),
+                                    });
+                                    tool_output
+                                }
+                                Err(e) => {
+                                    warn!(tool = %name, error = %e, "Tool call failed");
+                                    let result = e.to_tool_result(&name);
+                                    emit(AgentMessage::Error {
+                                        message: format!("Tool {} failed: {}", name, e),
+                                        details: Some(json!({
+                                            "arguments": arguments,
+                                            "result": result,
+                                        })),
+                                        kind: None,
+                                    });
+                                    // The model sees the structured error and can retry next round
+                                    result.to_string()
+                                }
+                            };
+
+                            tool_calls.push(json!({
+                                "function": { "name": name, "arguments": arguments }
+                            }));
+                            output.changes.push(RulingChange {
+                                name: name.clone(),
+                                arguments: arguments.to_string(),
+                                output: tool_output.clone(),
+                            });
+                            tool_results.push(ChatMessage::tool_result(&name, tool_output));
+                        }
+                        StreamChunk::Done(usage) => {
+                            debug!("Stream done");
+                            output.usage.add(&usage);
+                            break;
+                        }
+                    },
+                    Err(e) => {
+                        error!(error = %e, "Stream error");
+                        emit(AgentMessage::Error {
+                            message: format!("Stream error: {}", e),
+                            details: None,
+                            kind: ErrorKind::of(&*e),
+                        });
+                        stream_failed = true;
+                        break;
+                    }
+                }
+            }
+            output.text.push_str(&round_text);
+
+            if tool_calls.is_empty() || stream_failed {
+                // Add assistant response to history
+                if !round_text.is_empty() {
+                    output.messages.push(ChatMessage::new("assistant", round_text));
+                }
+                break;
+            }
+
+            // Record the calls and their results so the next round can build on them
+            let round_messages = std::iter::once(ChatMessage {
+                tool_calls: Some(tool_calls),
+                ..ChatMessage::new("assistant", round_text)
+            })
+            .chain(tool_results);
+            for message in round_messages {
+                messages.push(message.clone());
+                output.messages.push(message);
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Hand the player's words to an NPC's agent, streaming its reply as dialogue.
+    /// Returns the NPC's name, the line it was given and its reply.
+    async fn talk_to_npc<F>(
+        &self,
+        arguments: &Value,
+        state: &GameState,
+        emit: &mut F,
+    ) -> Result<(String, String, String), ToolError>
+    where
+        F: FnMut(AgentMessage) + Send,
+    {
+        let definition = tool_definition(&self.npcs);
+        validate_arguments(&definition.function.parameters, arguments).map_err(ToolError::InvalidArguments)?;
+        let args: TalkToNpcArgs =
+            serde_json::from_value(arguments.clone()).map_err(|e| ToolError::Failed(e.to_string()))?;
+        let npc = self
+            .npcs
+            .iter()
+            .find(|n| n.name == args.npc)
+            .ok_or_else(|| ToolError::Failed(format!("No character named {}", args.npc)))?;
+
+        let memory = self.npc_memory.get(&npc.name).cloned().unwrap_or_default();
+        let player = match state.character.name.as_str() {
+            "" => "The player",
+            name => name,
+        };
+        let line = format!("[{}, {}] {}: {}", state.clock, state.location, player, args.message);
+        info!(npc = %npc.name, "Talking to NPC");
+        let reply = npc
+            .reply(&memory, line.clone(), |content| {
+                emit(AgentMessage::Dialogue {
+                    speaker: npc.name.clone(),
+                    content,
+                })
+            })
+            .await
+            .map_err(|e| ToolError::Failed(e.to_string()))?;
+        Ok((npc.name.clone(), line, reply))
+    }
+
+    fn remember_conversations(&mut self, output: &StageOutput) {
+        for (npc, said, reply) in &output.conversations {
+            self.npc_memory
+                .entry(npc.clone())
+                .or_default()
+                .record(said.clone(), reply.clone());
+        }
+    }
+
+    /// Built-in and script tools, plus `talk_to_npc` when the scenario has NPCs
+    fn tool_definitions(&self) -> Vec<Tool> {
+        let mut tools = self.tools.definitions();
+        if !self.npcs.is_empty() {
+            tools.push(tool_definition(&self.npcs));
+        }
+        tools
+    }
+
+    /// The arbiter's ruling as a message for the narrator
+    fn format_ruling(&self, ruling: &StageOutput, state: &GameState) -> Result<String, Box<dyn Error + Send + Sync>> {
+        self.prompts.render_ruling(&RulingPromptContext {
+            ruling: ruling.text.trim(),
+            changes: &ruling.changes,
+            state,
+            time: state.clock.to_string(),
+            settings: self.prompt_settings(),
+        })
+    }
+
+    /// Past turns relevant to `action`, skipping those still in the conversation verbatim.
+    /// Memory is best-effort: without an embedding model the game just goes on without it.
+    async fn recall(&self, action: &str, turn_number: u32) -> Vec<RecalledMemory> {
+        let verbatim_turns = self.conversation_history.iter().filter(|m| m.role == "user").count();
+        let before_turn = turn_number.saturating_sub(verbatim_turns as u32);
+        if !self.memory.chunks.iter().any(|c| c.turn_number < before_turn) {
+            return Vec::new();
+        }
+        let query = match self.client.embed(vec![action.to_string()]).await {
+            Ok(mut embeddings) => embeddings.remove(0),
+            Err(e) => {
+                warn!(error = %e, "Failed to embed action for memory recall");
+                return Vec::new();
+            }
+        };
+        let model = self.client.embedding_model();
+        self.memory
+            .search(model, &query, RECALLED_MEMORIES, before_turn)
+            .into_iter()
+            .map(|c| RecalledMemory {
+                turn_number: c.turn_number,
+                text: c.text.clone(),
+            })
+            .collect()
+    }
+
+    /// Index a finished turn in long-term memory
+    async fn remember(&mut self, turn_number: u32, action: &str, story_text: &str) {
+        let chunks = chunk_turn(action, story_text);
+        match self.client.embed(chunks.clone()).await {
+            Ok(embeddings) => {
+                let model = self.client.embedding_model().to_string();
+                self.memory.add(&model, turn_number, chunks, embeddings);
+                debug!(turn = turn_number, chunks = self.memory.chunks.len(), "Indexed turn in memory");
+            }
+            Err(e) => warn!(turn = turn_number, error = %e, "Failed to index turn in memory"),
+        }
+    }
+
+    /// Messages that would be sent to the model if the player took `action` now
+    pub async fn preview_messages(
+        &self,
+        action: &str,
+        state: &GameState,
+        history: &[TurnData],
+    ) -> Result<Vec<ChatMessage>, Box<dyn Error + Send + Sync>> {
+        let memories = self.recall(action, history.len() as u32).await;
+        let world_info = self.select_world_info(action, history);
+        let mut messages = self.request_messages(&world_info);
+        messages.push(ChatMessage::new(
+            "user",
+            self.format_user_message(action, state, history, memories, &world_info)?,
+        ));
+        Ok(messages)
+    }
+
+    /// Lorebook entries triggered by the action or the last few turns
+    fn select_world_info(&self, action: &str, history: &[TurnData]) -> Vec<LoreEntry> {
+        let recent = &history[history.len().saturating_sub(self.lorebook.scan_depth)..];
+        let texts: Vec<&str> = std::iter::once(action)
+            .chain(recent.iter().map(|t| t.story_text.as_str()))
+            .collect();
+        let entries: Vec<LoreEntry> = self.lorebook.select(&texts).into_iter().cloned().collect();
+        if !entries.is_empty() {
+            let titles: Vec<&str> = entries.iter().map(|e| e.title.as_str()).collect();
+            debug!(entries = %titles.join(", "), "Lorebook entries triggered");
+        }
+        entries
+    }
+
+    /// Conversation as sent to the model: the story summary and any system-position
+    /// world info follow the system prompt
+    fn request_messages(&self, world_info: &[LoreEntry]) -> Vec<ChatMessage> {
+        let mut messages = self.conversation_history.clone();
+        let mut insert_at = 1.min(messages.len());
+        if !self.story_summary.is_empty() {
+            let summary = format!("Summary of the story so far:\n{}", self.story_summary);
+            messages.insert(insert_at, ChatMessage::new("system", summary));
+            insert_at += 1;
+        }
+        let system_info: Vec<String> = world_info
+            .iter()
+            .filter(|e| e.position == InsertionPosition::System)
+            .map(|e| format!("- {}: {}", e.title, e.content))
+            .collect();
+        if !system_info.is_empty() {
+            let info = format!("World info:\n{}", system_info.join("\n"));
+            messages.insert(insert_at, ChatMessage::new("system", info));
+        }
+        messages
+    }
+
+    /// Fold the oldest turns into the story summary once the conversation no longer
+    /// fits the budget. The system prompt and the most recent turns stay verbatim.
+    async fn compact_history(&mut self, tools: &[Tool], world_info: &[LoreEntry]) {
+        let tools_cost = estimate_tokens(&serde_json::to_string(tools).unwrap_or_default());
+        let limit = self.budget.prompt_tokens().saturating_sub(tools_cost);
+        let request = self.request_messages(world_info);
+        if estimate_messages(&request) <= limit {
+            return;
+        }
+
+        // Each turn starts with a player message
+        let turn_starts: Vec<usize> = self
+            .conversation_history
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.role == "user")
+            .map(|(i, _)| i)
+            .collect();
+        let Some(&first_turn) = turn_starts.first() else {
+            return;
+        };
+
+        // Fold everything but the recent turns, and more if those alone are still too
+        // large, but always keep the turn being played
+        // Everything before the first turn, including the summary and system-position world info
+        let prefix_cost = estimate_messages(&request) - estimate_messages(&self.conversation_history[first_turn..]);
+        let mut fold = turn_starts.len().saturating_sub(self.budget.recent_turns.max(1));
+        while fold + 1 < turn_starts.len()
+            && prefix_cost + estimate_messages(&self.conversation_history[turn_starts[fold]..]) > limit
+        {
+            fold += 1;
+        }
+        if fold == 0 {
+            return;
+        }
+
+        let folded_end = turn_starts[fold];
+        match self.summarize(&self.conversation_history[first_turn..folded_end]).await {
+            Ok(summary) => {
+                info!(turns = fold, "Folded old turns into the story summary");
+                self.story_summary = summary;
+                self.conversation_history.drain(first_turn..folded_end);
+            }
+            Err(e) => warn!(error = %e, "Failed to summarize old turns"),
+        }
+    }
+
+    /// Ask the model to merge `messages` into the current story summary
+    async fn summarize(&self, messages: &[ChatMessage]) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let transcript = messages
+            .iter()
+            .filter(|m| !m.content.is_empty())
+            .map(|m| TranscriptEntry {
+                speaker: match m.role.as_str() {
+                    "user" => "Player".to_string(),
+                    "assistant" => "Narrator".to_string(),
+                    "system" => "Arbiter".to_string(),
+                    "tool" => format!("Tool {}", m.tool_name.as_deref().unwrap_or("result")),
+                    other => other.to_string(),
+                },
+                content: m.content.clone(),
+            })
+            .collect();
+        let request = self.prompts.render_summary(&SummaryPromptContext {
+            previous_summary: &self.story_summary,
+            transcript,
+            settings: self.prompt_settings(),
+        })?;
+        let summary = self.client.chat(vec![ChatMessage::new("user", request)]).await?;
+        if summary.is_empty() {
+            return Err("The model returned an empty summary".into());
+        }
+        Ok(summary)
+    }
+
+    fn prompt_settings(&self) -> PromptSettings {
+        PromptSettings {
+            model: self.client.model().to_string(),
+            ollama_url: self.client.base_url().to_string(),
+        }
+    }
+
+    /// Create the system prompt for the dungeon master
+    fn create_system_prompt(&self, scenario: &Scenario, state: &GameState) -> Result<String, Box<dyn Error + Send + Sync>> {
+        self.prompts.render_system(&self.system_prompt_context(scenario, state))
+    }
+
+    fn system_prompt_context<'a>(&self, scenario: &'a Scenario, state: &'a GameState) -> SystemPromptContext<'a> {
+        let tools = self
+            .tool_definitions()
+            .into_iter()
+            .map(|t| ToolInfo {
+                name: t.function.name,
+                description: t.function.description,
+            })
+            .collect();
+        SystemPromptContext {
+            persona: scenario.persona.as_deref().unwrap_or(DEFAULT_PERSONA).trim(),
+            lore: scenario.lore.as_deref().map(str::trim),
+            tools,
+            scenario: scenario.summary(),
+            character: &state.character,
+            state,
+            pipeline: self.pipeline.is_some(),
+            settings: self.prompt_settings(),
+        }
+    }
+
+    /// Format user message with current state context
+    fn format_user_message(
+        &self,
+        action: &str,
+        state: &GameState,
+        history: &[TurnData],
+        memories: Vec<RecalledMemory>,
+        world_info: &[LoreEntry],
+    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let world_info_at = |position| world_info.iter().filter(|e| e.position == position).collect();
+        let due_events = state
+            .triggered_events
+            .iter()
+            .map(|e| DueEvent {
+                at: e.at.to_string(),
+                description: e.description.clone(),
+            })
+            .collect();
+        self.prompts.render_user(&UserPromptContext {
+            action,
+            state,
+            time: state.clock.to_string(),
+            exits: state.world.neighbours(&state.location),
+            variables: state.variables.render(),
+            due_events,
+            memories,
+            world_info_before: world_info_at(InsertionPosition::BeforeAction),
+            world_info_after: world_info_at(InsertionPosition::AfterAction),
+            recent_turns: &history[history.len().saturating_sub(RECENT_TURNS)..],
+            settings: self.prompt_settings(),
+        })
+    }
+
+    /// Extract choices from the model's response
+    fn extract_choices(&self, text: &str) -> Vec<String> {
+        let mut choices = Vec::new();
+        
+        // Try to extract numbered choices from the text
+        for line in text.lines() {
+            let trimmed = line.trim();
+            // Match patterns like "1. ", "1) ", "1: "
+            if let Some(rest) = trimmed.strip_prefix("1.").or_else(|| trimmed.strip_prefix("1)").or_else(|| trimmed.strip_prefix("1:"))) {
+                choices.push(rest.trim().to_string());
+            } else if let Some(rest) = trimmed.strip_prefix("2.").or_else(|| trimmed.strip_prefix("2)").or_else(|| trimmed.strip_prefix("2:"))) {
+                choices.push(rest.trim().to_string());
+            } else if let Some(rest) = trimmed.strip_prefix("3.").or_else(|| trimmed.strip_prefix("3)").or_else(|| trimmed.strip_prefix("3:"))) {
+                choices.push(rest.trim().to_string());
+            }
+        }
+
+        // If we couldn't extract choices, provide defaults
+        if choices.len() < 3 {
+            choices = DEFAULT_CHOICES.iter().map(|c| c.to_string()).collect();
+        }
+
+        choices.truncate(3); // Ensure exactly 3 choices
+        choices
+    }
+}
@@ -0,0 +1,248 @@
+use crate::ollama::{ErrorKind, OllamaError};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use bytes::Bytes;
+use futures::stream::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio_stream::Stream;
+use tracing::{error, info, warn};
+
+/// A response body as it arrived over the network
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, io::Error>> + Send>>;
+
+/// Records every request to Ollama and the raw bytes of each reply to a file, or
+/// plays such a recording back in place of Ollama. Replies are replayed in order,
+/// chunk for chunk, so a playthrough can be re-run offline through the real parser
+/// and agent. Requests that never reached Ollama are recorded too, and fail the
+/// same way on replay. Clones share the same tape.
+#[derive(Clone)]
+pub struct Cassette {
+    tape: Arc<Mutex<Tape>>,
+    replaying: bool,
+}
+
+struct Tape {
+    path: PathBuf,
+    interactions: Vec<Interaction>,
+    /// Next interaction to play back
+    position: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// One request and the reply it got
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    /// Path of the Ollama endpoint, e.g. `/api/chat`
+    endpoint: String,
+    request: Value,
+    status: u16,
+    /// The body, one entry per network chunk
+    chunks: Vec<RawChunk>,
+    /// Set when the connection failed partway through the body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// Set, along with `error`, when the request got no reply at all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    failed: Option<ErrorKind>,
+}
+
+/// A network chunk: kept as text when it is valid UTF-8 so cassettes stay readable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawChunk {
+    Text(String),
+    Binary { base64: String },
+}
+
+impl RawChunk {
+    fn new(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self::Text(text.to_string()),
+            Err(_) => Self::Binary {
+                base64: BASE64.encode(bytes),
+            },
+        }
+    }
+
+    fn bytes(&self) -> Result<Bytes, io::Error> {
+        match self {
+            Self::Text(text) => Ok(Bytes::from(text.clone())),
+            Self::Binary { base64 } => BASE64
+                .decode(base64)
+                .map(Bytes::from)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+}
+
+impl Cassette {
+    /// Start a new recording; `path` is overwritten as interactions complete
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self::with_tape(path.into(), Vec::new(), false)
+    }
+
+    /// Play back a recording made with [`Cassette::record`]
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = path.into();
+        let content = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let file: CassetteFile =
+            serde_json::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+        info!(requests = file.interactions.len(), path = %path.display(), "Replaying cassette");
+        Ok(Self::with_tape(path, file.interactions, true))
+    }
+
+    fn with_tape(path: PathBuf, interactions: Vec<Interaction>, replaying: bool) -> Self {
+        Self {
+            tape: Arc::new(Mutex::new(Tape {
+                path,
+                interactions,
+                position: 0,
+            })),
+            replaying,
+        }
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replaying
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.tape.lock().unwrap().path.clone()
+    }
+
+    /// The next recorded reply, which must be for the same endpoint. A request that
+    /// differs from the recorded one is only reported, as that is often what is
+    /// being debugged.
+    pub fn play(&self, endpoint: &str, request: &Value) -> Result<(u16, ByteStream), Box<dyn Error + Send + Sync>> {
+        let mut tape = self.tape.lock().unwrap();
+        let position = tape.position;
+        let interaction = tape
+            .interactions
+            .get(position)
+            .cloned()
+            .ok_or_else(|| format!("The cassette has no reply for request {} to {}", position + 1, endpoint))?;
+        if interaction.endpoint != endpoint {
+            return Err(format!(
+                "Request {} went to {}, but the cassette recorded {}",
+                position + 1,
+                endpoint,
+                interaction.endpoint
+            )
+            .into());
+        }
+        if &interaction.request != request {
+            warn!(request = position + 1, endpoint, "Request differs from the recording");
+        }
+        tape.position += 1;
+
+        if let Some(kind) = interaction.failed {
+            let message = interaction.error.unwrap_or_default();
+            return Err(OllamaError { kind, message }.into());
+        }
+        let error = interaction
+            .error
+            .map(|message| Err(io::Error::new(io::ErrorKind::ConnectionAborted, message)));
+        let chunks = interaction.chunks.iter().map(RawChunk::bytes).collect::<Vec<_>>();
+        let body = futures::stream::iter(chunks.into_iter().chain(error));
+        Ok((interaction.status, Box::pin(body)))
+    }
+
+    /// Pass `body` through, recording the request and every chunk of the reply.
+    /// The cassette is written once the body has been read or dropped.
+    pub fn record_reply(&self, endpoint: &str, request: Value, status: u16, body: ByteStream) -> ByteStream {
+        let index = {
+            let mut tape = self.tape.lock().unwrap();
+            tape.interactions.push(Interaction {
+                endpoint: endpoint.to_string(),
+                request,
+                status,
+                chunks: Vec::new(),
+                error: None,
+                failed: None,
+            });
+            tape.interactions.len() - 1
+        };
+        let recorder = Recorder {
+            cassette: self.clone(),
+            index,
+        };
+        Box::pin(body.map(move |chunk| {
+            recorder.add(&chunk);
+            chunk
+        }))
+    }
+
+    /// Record a request that failed before Ollama replied, and write the cassette
+    pub fn record_failure(&self, endpoint: &str, request: Value, error: &OllamaError) {
+        self.tape.lock().unwrap().interactions.push(Interaction {
+            endpoint: endpoint.to_string(),
+            request,
+            status: 0,
+            chunks: Vec::new(),
+            error: Some(error.message.clone()),
+            failed: Some(error.kind),
+        });
+        self.save();
+    }
+
+    fn save(&self) {
+        let tape = self.tape.lock().unwrap();
+        let file = CassetteFile {
+            interactions: tape.interactions.clone(),
+        };
+        if let Err(e) = write_file(&tape.path, &file) {
+            error!(path = %tape.path.display(), error = %e, "Failed to write cassette");
+        }
+    }
+}
+
+impl fmt::Debug for Cassette {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cassette")
+            .field("path", &self.path())
+            .field("replaying", &self.replaying)
+            .finish()
+    }
+}
+
+/// Adds a reply's chunks to its interaction, and writes the cassette when the reply is dropped
+struct Recorder {
+    cassette: Cassette,
+    index: usize,
+}
+
+impl Recorder {
+    fn add(&self, chunk: &Result<Bytes, io::Error>) {
+        let mut tape = self.cassette.tape.lock().unwrap();
+        let interaction = &mut tape.interactions[self.index];
+        match chunk {
+            Ok(bytes) => interaction.chunks.push(RawChunk::new(bytes)),
+            Err(e) => interaction.error = Some(e.to_string()),
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        self.cassette.save();
+    }
+}
+
+fn write_file(path: &Path, file: &CassetteFile) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(file)?)?;
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+/// The player character, created before turn 0
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+#[ts(export)]
+pub struct CharacterSheet {
+    pub name: String,
+    pub pronouns: String,
+    pub background: String,
+    pub stats: BTreeMap<String, i32>,
+    pub outfit: String,
+}
+
+/// A stat the player can raise during character creation
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct StatDef {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub min: i32,
+    pub max: i32,
+    pub default: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BackgroundOption {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Character creation options offered by a scenario
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+#[ts(export)]
+pub struct CharacterTemplate {
+    pub backgrounds: Vec<BackgroundOption>,
+    pub stats: Vec<StatDef>,
+    /// Points the player may spend raising stats above their defaults
+    pub stat_points: i32,
+    pub outfits: Vec<String>,
+    /// Sheet used when the player skips character creation
+    pub default: CharacterSheet,
+}
+
+impl CharacterTemplate {
+    /// Check a submitted sheet against the template, filling in anything left blank
+    pub fn finalize(&self, mut sheet: CharacterSheet) -> Result<CharacterSheet, String> {
+        sheet.name = sheet.name.trim().to_string();
+        if sheet.name.is_empty() {
+            return Err("Your character needs a name".to_string());
+        }
+        if sheet.pronouns.trim().is_empty() {
+            sheet.pronouns = "they/them".to_string();
+        }
+        if sheet.outfit.trim().is_empty() {
+            sheet.outfit = self.outfits.first().cloned().unwrap_or_default();
+        }
+
+        if let Some(unknown) = sheet.stats.keys().find(|k| !self.stats.iter().any(|s| &s.name == *k)) {
+            return Err(format!("Unknown stat '{}'", unknown));
+        }
+        let mut spent = 0;
+        for stat in &self.stats {
+            let value = *sheet.stats.entry(stat.name.clone()).or_insert(stat.default);
+            if value < stat.min || value > stat.max {
+                return Err(format!("{} must be between {} and {}", stat.name, stat.min, stat.max));
+            }
+            spent += value - stat.default;
+        }
+        if spent > self.stat_points {
+            return Err(format!("{} stat points spent, only {} available", spent, self.stat_points));
+        }
+        Ok(sheet)
+    }
+
+    /// Character for players who don't create one
+    pub fn default_sheet(&self) -> CharacterSheet {
+        let mut sheet = self.default.clone();
+        if sheet.name.is_empty() {
+            sheet.name = "Traveler".to_string();
+        }
+        self.finalize(sheet.clone()).unwrap_or(sheet)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(name: &str) -> StatDef {
+        StatDef {
+            name: name.to_string(),
+            description: String::new(),
+            min: 1,
+            max: 5,
+            default: 2,
+        }
+    }
+
+    fn template() -> CharacterTemplate {
+        CharacterTemplate {
+            stats: vec![stat("Might"), stat("Wits")],
+            stat_points: 3,
+            outfits: vec!["Traveling cloak".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn sheet(stats: &[(&str, i32)]) -> CharacterSheet {
+        CharacterSheet {
+            name: "  Ada ".to_string(),
+            stats: stats.iter().map(|(name, value)| (name.to_string(), *value)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fills_in_defaults_for_blank_fields() {
+        let sheet = template().finalize(sheet(&[])).unwrap();
+        assert_eq!(sheet.name, "Ada");
+        assert_eq!(sheet.pronouns, "they/them");
+        assert_eq!(sheet.outfit, "Traveling cloak");
+        assert_eq!(sheet.stats, BTreeMap::from([("Might".to_string(), 2), ("Wits".to_string(), 2)]));
+    }
+
+    #[test]
+    fn accepts_spending_every_point() {
+        assert!(template().finalize(sheet(&[("Might", 5)])).is_ok());
+        assert!(template().finalize(sheet(&[("Might", 4), ("Wits", 3)])).is_ok());
+    }
+
+    #[test]
+    fn rejects_spending_more_points_than_available() {
+        let error = template().finalize(sheet(&[("Might", 5), ("Wits", 3)])).unwrap_err();
+        assert_eq!(error, "4 stat points spent, only 3 available");
+    }
+
+    #[test]
+    fn rejects_stats_out_of_bounds() {
+        assert_eq!(template().finalize(sheet(&[("Might", 6)])).unwrap_err(), "Might must be between 1 and 5");
+        assert_eq!(template().finalize(sheet(&[("Wits", 0)])).unwrap_err(), "Wits must be between 1 and 5");
+    }
+
+    #[test]
+    fn rejects_unknown_stats_and_blank_names() {
+        assert_eq!(template().finalize(sheet(&[("Luck", 3)])).unwrap_err(), "Unknown stat 'Luck'");
+        let nameless = CharacterSheet {
+            name: "   ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(template().finalize(nameless).unwrap_err(), "Your character needs a name");
+    }
+}
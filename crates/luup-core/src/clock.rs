@@ -0,0 +1,199 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use ts_rs::TS;
+
+const MINUTES_PER_HOUR: u64 = 60;
+const MINUTES_PER_DAY: u64 = 24 * MINUTES_PER_HOUR;
+/// Last minute of the last day a `u32` can count
+const MAX_MINUTES: u64 = u32::MAX as u64 * MINUTES_PER_DAY - 1;
+
+/// In-game calendar time, counted from day 1 at midnight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, TS)]
+#[ts(as = "ClockView")]
+pub struct GameClock {
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self { day: 1, hour: 8, minute: 0 }
+    }
+}
+
+impl GameClock {
+    /// Build a clock, normalising overflowing hours and minutes into later days
+    pub fn new(day: u32, hour: u32, minute: u32) -> Self {
+        Self::from_minutes(
+            (day.max(1) as u64 - 1) * MINUTES_PER_DAY
+                + hour as u64 * MINUTES_PER_HOUR
+                + minute as u64,
+        )
+    }
+
+    /// Minutes elapsed since day 1, 00:00
+    pub fn total_minutes(&self) -> u64 {
+        (self.day.saturating_sub(1) as u64) * MINUTES_PER_DAY
+            + self.hour as u64 * MINUTES_PER_HOUR
+            + self.minute as u64
+    }
+
+    /// Clock at `total` minutes since day 1, 00:00; stops at the last representable day
+    pub fn from_minutes(total: u64) -> Self {
+        let total = total.min(MAX_MINUTES);
+        Self {
+            day: (total / MINUTES_PER_DAY) as u32 + 1,
+            hour: ((total % MINUTES_PER_DAY) / MINUTES_PER_HOUR) as u32,
+            minute: (total % MINUTES_PER_HOUR) as u32,
+        }
+    }
+
+    /// Move the clock forward by the given number of minutes
+    pub fn advance(&mut self, minutes: u64) {
+        *self = Self::from_minutes(self.total_minutes().saturating_add(minutes));
+    }
+
+    /// Coarse label used in prompts and the UI
+    pub fn time_of_day(&self) -> &'static str {
+        match self.hour {
+            5..=11 => "Morning",
+            12..=16 => "Afternoon",
+            17..=20 => "Evening",
+            _ => "Night",
+        }
+    }
+
+    /// Map a legacy time-of-day string onto a representative clock time on day 1
+    pub fn from_label(label: &str) -> Self {
+        let hour = match label.to_ascii_lowercase().as_str() {
+            "afternoon" => 14,
+            "evening" => 18,
+            "night" => 22,
+            _ => 8,
+        };
+        Self { day: 1, hour, minute: 0 }
+    }
+}
+
+impl fmt::Display for GameClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Day {}, {:02}:{:02} ({})",
+            self.day,
+            self.hour,
+            self.minute,
+            self.time_of_day()
+        )
+    }
+}
+
+/// Serialized form of a clock, with the derived label so the frontend doesn't need to recompute it
+#[derive(Serialize, TS)]
+#[ts(export, rename = "GameClock")]
+pub struct ClockView {
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub time_of_day: String,
+}
+
+impl Serialize for GameClock {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ClockView {
+            day: self.day,
+            hour: self.hour,
+            minute: self.minute,
+            time_of_day: self.time_of_day().to_string(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Accepts either the structured form or the old "Morning"/"Night" strings
+impl<'de> Deserialize<'de> for GameClock {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Clock { day: u32, hour: u32, minute: u32 },
+            Legacy(String),
+        }
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Clock { day, hour, minute } => GameClock::new(day, hour, minute),
+            Repr::Legacy(label) => GameClock::from_label(&label),
+        })
+    }
+}
+
+/// Something the story has scheduled to happen at a given time
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScheduledEvent {
+    pub at: GameClock,
+    pub description: String,
+}
+
+/// Remove and return every event whose time is at or before `now`, in chronological order
+pub fn take_due_events(events: &mut Vec<ScheduledEvent>, now: &GameClock) -> Vec<ScheduledEvent> {
+    let (mut due, pending): (Vec<_>, Vec<_>) = events.drain(..).partition(|e| e.at <= *now);
+    *events = pending;
+    due.sort_by_key(|e| e.at);
+    due
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::GameState;
+
+    #[test]
+    fn advancing_past_midnight_rolls_over_to_the_next_day() {
+        let mut clock = GameClock::new(1, 23, 30);
+        clock.advance(45);
+        assert_eq!(clock, GameClock { day: 2, hour: 0, minute: 15 });
+    }
+
+    #[test]
+    fn from_minutes_splits_at_day_and_hour_boundaries() {
+        assert_eq!(GameClock::from_minutes(0), GameClock { day: 1, hour: 0, minute: 0 });
+        assert_eq!(GameClock::from_minutes(59), GameClock { day: 1, hour: 0, minute: 59 });
+        assert_eq!(GameClock::from_minutes(60), GameClock { day: 1, hour: 1, minute: 0 });
+        assert_eq!(GameClock::from_minutes(1439), GameClock { day: 1, hour: 23, minute: 59 });
+        assert_eq!(GameClock::from_minutes(1440), GameClock { day: 2, hour: 0, minute: 0 });
+        assert_eq!(GameClock::new(1, 25, 70), GameClock { day: 2, hour: 2, minute: 10 });
+    }
+
+    #[test]
+    fn huge_advances_stop_at_the_last_day() {
+        let mut clock = GameClock::default();
+        clock.advance(u64::MAX);
+        assert_eq!(clock, GameClock { day: u32::MAX, hour: 23, minute: 59 });
+    }
+
+    #[test]
+    fn time_of_day_labels_change_at_their_first_hour() {
+        let label = |hour| GameClock::new(1, hour, 0).time_of_day();
+        assert_eq!([label(4), label(5), label(11)], ["Night", "Morning", "Morning"]);
+        assert_eq!([label(12), label(16), label(17)], ["Afternoon", "Afternoon", "Evening"]);
+        assert_eq!([label(20), label(21), label(0)], ["Evening", "Night", "Night"]);
+    }
+
+    #[test]
+    fn legacy_labels_load_as_clock_times() {
+        let clock: GameClock = serde_json::from_str("\"Evening\"").unwrap();
+        assert_eq!(clock, GameClock { day: 1, hour: 18, minute: 0 });
+
+        let state: GameState = serde_json::from_str(r#"{"time": "morning"}"#).unwrap();
+        assert_eq!(state.clock, GameClock { day: 1, hour: 8, minute: 0 });
+    }
+
+    #[test]
+    fn structured_clocks_round_trip() {
+        let clock = GameClock::new(3, 14, 5);
+        let json = serde_json::to_value(clock).unwrap();
+        assert_eq!(json["time_of_day"], "Afternoon");
+        assert_eq!(serde_json::from_value::<GameClock>(json).unwrap(), clock);
+    }
+}
@@ -0,0 +1,79 @@
+use crate::ollama::ChatMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+/// Tokens added per message for role markers and separators
+const MESSAGE_OVERHEAD: usize = 4;
+
+/// How much of a model's context window a conversation may use
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextBudget {
+    /// Context window requested from Ollama (`num_ctx`)
+    pub context_tokens: usize,
+    /// Tokens kept free for the model's reply
+    pub response_tokens: usize,
+    /// Most recent turns that are never folded into the summary
+    pub recent_turns: usize,
+}
+
+impl Default for ContextBudget {
+    fn default() -> Self {
+        Self {
+            context_tokens: 4096,
+            response_tokens: 1024,
+            recent_turns: 4,
+        }
+    }
+}
+
+impl ContextBudget {
+    /// Budget for a model: a user override if there is one, else a built-in default
+    pub fn for_model(model: &str, overrides: &HashMap<String, ContextBudget>) -> Self {
+        if let Some(budget) = overrides.get(model) {
+            return budget.clone();
+        }
+        let family = model.split(':').next().unwrap_or(model);
+        let context_tokens = match family {
+            "qwen3" | "qwen2.5" | "llama3.1" | "llama3.2" | "mistral-nemo" | "gemma3" => 16384,
+            "mistral" | "llama3" => 8192,
+            _ => return Self::default(),
+        };
+        Self {
+            context_tokens,
+            recent_turns: 6,
+            ..Self::default()
+        }
+    }
+
+    /// Tokens available for the prompt itself
+    pub fn prompt_tokens(&self) -> usize {
+        self.context_tokens.saturating_sub(self.response_tokens)
+    }
+}
+
+/// Per-model budgets from a JSON file such as `{"qwen3:8b": {"context_tokens": 32768}}`
+pub fn load_budgets(path: &Path) -> Result<HashMap<String, ContextBudget>, Box<dyn Error + Send + Sync>> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// Rough token count: about four characters per token for English prose
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+pub fn estimate_messages(messages: &[ChatMessage]) -> usize {
+    messages
+        .iter()
+        .map(|m| {
+            let tool_calls = m
+                .tool_calls
+                .as_ref()
+                .map(|calls| estimate_tokens(&serde_json::to_string(calls).unwrap_or_default()))
+                .unwrap_or(0);
+            MESSAGE_OVERHEAD + estimate_tokens(&m.content) + tool_calls
+        })
+        .sum()
+}
@@ -0,0 +1,345 @@
+use crate::agent::{Agent, AgentMessage};
+use crate::cassette::Cassette;
+use crate::character::CharacterSheet;
+use crate::context::{load_budgets, ContextBudget};
+use crate::logging::redact;
+use crate::ollama::{load_policy, ChatMessage, Tool};
+use crate::pipeline::{load_pipeline, PipelineConfig};
+use crate::prompts::PromptTemplates;
+use crate::scenario::{discover_scenarios, Scenario, ScenarioSummary};
+use crate::session::{self, new_session_id, now_timestamp, read_save, write_save, SaveFile, SaveGame};
+use crate::state::{GameState, TurnData};
+use crate::tools::ToolError;
+use crate::usage::SessionStats;
+use crate::world::WorldMap;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tracing::{info, info_span, Instrument, Span};
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+/// Scenario used when the player doesn't pick one
+pub const DEFAULT_SCENARIO: &str = "fantasy";
+
+/// A request the engine turned down, such as an unknown scenario or an invalid character,
+/// as opposed to a failure of the engine itself
+#[derive(Debug)]
+pub struct InvalidRequest(pub String);
+
+impl fmt::Display for InvalidRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for InvalidRequest {}
+
+/// Where the engine finds scenario packs, the player's overrides and saves
+#[derive(Debug, Clone, Default)]
+pub struct EngineConfig {
+    /// Directories searched for scenario packs; later roots override earlier ones
+    pub scenario_roots: Vec<PathBuf>,
+    /// App data directory holding saves, prompt overrides, context_budgets.json and pipeline.json
+    pub data_dir: Option<PathBuf>,
+    /// Ollama host and port; the client's default when unset
+    pub ollama_url: Option<String>,
+    /// Storytelling model; the client's default when unset
+    pub model: Option<String>,
+    /// Record every game's model traffic, or replay a recording instead of calling Ollama
+    pub cassette: Option<Cassette>,
+    /// Seed for the scripted tools' dice, so playthroughs can be repeated; random when unset
+    pub dice_seed: Option<u64>,
+}
+
+/// Starts, loads and saves games. Cheap to clone and free of UI concerns.
+#[derive(Debug, Clone)]
+pub struct Engine {
+    config: EngineConfig,
+}
+
+impl Engine {
+    pub fn new(config: EngineConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+
+    /// Directory holding save files
+    pub fn saves_dir(&self) -> Result<PathBuf> {
+        let data_dir = self.config.data_dir.as_deref().ok_or("No data directory to keep saves in")?;
+        Ok(saves_dir(data_dir))
+    }
+
+    /// Every scenario pack found in the scenario roots
+    pub async fn scenarios(&self) -> Vec<Scenario> {
+        let roots = self.config.scenario_roots.clone();
+        tokio::task::spawn_blocking(move || discover_scenarios(&roots))
+            .await
+            .unwrap_or_default()
+    }
+
+    pub async fn list_scenarios(&self) -> Vec<ScenarioSummary> {
+        self.scenarios().await.iter().map(Scenario::summary).collect()
+    }
+
+    pub async fn find_scenario(&self, scenario_id: &str) -> Result<Scenario> {
+        self.scenarios()
+            .await
+            .into_iter()
+            .find(|s| s.id() == scenario_id)
+            .ok_or_else(|| format!("Scenario '{}' not found", scenario_id).into())
+    }
+
+    /// Saved games, most recently played first
+    pub async fn list_saves(&self) -> Vec<SaveGame> {
+        match self.saves_dir() {
+            Ok(dir) => session::list_saves(&dir).await,
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Start a game in a scenario; without a character the scenario's default one is used
+    pub async fn new_game(&self, scenario_id: Option<&str>, character: Option<CharacterSheet>) -> Result<Game> {
+        let scenario = self
+            .find_scenario(scenario_id.unwrap_or(DEFAULT_SCENARIO))
+            .await
+            .map_err(|e| InvalidRequest(e.to_string()))?;
+        let template = &scenario.manifest.character;
+        let character = match character {
+            Some(sheet) => template.finalize(sheet).map_err(InvalidRequest)?,
+            None => template.default_sheet(),
+        };
+        info!(scenario = scenario.id(), "Starting scenario");
+
+        let mut agent = self.agent();
+        let state = self.prepare_agent(&mut agent, &scenario, character.clone())?;
+        let opening = &scenario.manifest.opening;
+        let history = vec![TurnData {
+            turn_number: 0,
+            story_text: opening.story_text.clone(),
+            choices: opening.choices.clone(),
+            game_state: state.clone(),
+            usage: None,
+        }];
+        let id = self.unused_session_id(scenario.id()).await;
+        Ok(Game {
+            span: session_span(&id, scenario.id()),
+            id,
+            name: format!("{}: {}", scenario.manifest.name, character.name),
+            scenario_id: scenario.id().to_string(),
+            agent,
+            state,
+            history,
+        })
+    }
+
+    /// Resume a saved game
+    pub async fn load_game(&self, save_id: &str) -> Result<Game> {
+        let save = read_save(&self.saves_dir()?, save_id).await?;
+        let scenario = self.find_scenario(&save.scenario_id).await?;
+        info!(save = %save.name, turns = save.history.len(), "Loading save");
+
+        let mut agent = self.agent();
+        self.prepare_agent(&mut agent, &scenario, save.state.character.clone())?;
+        agent.restore_session(save.agent);
+        Ok(Game {
+            span: session_span(&save.id, &save.scenario_id),
+            id: save.id,
+            name: save.name,
+            scenario_id: save.scenario_id,
+            agent,
+            state: save.state,
+            history: save.history,
+        })
+    }
+
+    /// Write a game to its save file
+    pub async fn save(&self, game: &Game) -> Result<()> {
+        write_save(&self.saves_dir()?, &game.save_file()).await
+    }
+
+    /// A new session id that no save is using yet, so starting a game never overwrites another
+    async fn unused_session_id(&self, scenario_id: &str) -> String {
+        loop {
+            let id = new_session_id(scenario_id);
+            match self.saves_dir() {
+                Ok(dir) if session::save_exists(&dir, &id).await => continue,
+                _ => return id,
+            }
+        }
+    }
+
+    fn agent(&self) -> Agent {
+        let mut agent = match &self.config.ollama_url {
+            Some(url) => Agent::with_ollama_url(url.clone()),
+            None => Agent::new(),
+        };
+        if let Some(model) = &self.config.model {
+            agent.set_model(model);
+        }
+        agent.set_cassette(self.config.cassette.clone());
+        if let Some(seed) = self.config.dice_seed {
+            agent.set_dice_seed(seed);
+        }
+        agent
+    }
+
+    /// Set the agent up for a scenario and player character: prompt templates, context
+    /// budget, pipeline, tools and system prompt. The player's overrides are read from
+    /// the data directory. Returns the scenario's initial game state.
+    fn prepare_agent(&self, agent: &mut Agent, scenario: &Scenario, character: CharacterSheet) -> Result<GameState> {
+        // Prompt templates: built-in, then the scenario's, then the player's own in <app data>/prompts
+        let mut prompt_dirs = vec![scenario.prompts_dir()];
+        // Per-model context budgets can be overridden in <app data>/context_budgets.json
+        let mut budgets = HashMap::new();
+        // The arbiter/narrator pipeline is configured in <app data>/pipeline.json
+        let mut pipeline = PipelineConfig::default();
+        // Timeouts and retries can be tuned in <app data>/ollama.json
+        if let Some(data_dir) = &self.config.data_dir {
+            prompt_dirs.push(data_dir.join("prompts"));
+            let budgets_file = data_dir.join("context_budgets.json");
+            if budgets_file.is_file() {
+                budgets = load_budgets(&budgets_file).map_err(|e| format!("{}: {}", budgets_file.display(), e))?;
+            }
+            let pipeline_file = data_dir.join("pipeline.json");
+            if pipeline_file.is_file() {
+                pipeline = load_pipeline(&pipeline_file).map_err(|e| format!("{}: {}", pipeline_file.display(), e))?;
+            }
+            let policy_file = data_dir.join("ollama.json");
+            if policy_file.is_file() {
+                let policy = load_policy(&policy_file).map_err(|e| format!("{}: {}", policy_file.display(), e))?;
+                agent.set_request_policy(policy);
+            }
+        }
+        let prompts = PromptTemplates::load(&prompt_dirs)?;
+
+        let budget = ContextBudget::for_model(agent.model(), &budgets);
+        agent.set_context_budget(budget);
+        agent.set_pipeline(pipeline);
+        agent.start_new_game(scenario, prompts, character)
+    }
+}
+
+/// Directory holding save files inside the app data directory
+pub fn saves_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("saves")
+}
+
+/// The span a game's turns are logged under
+fn session_span(id: &str, scenario_id: &str) -> Span {
+    info_span!("session", id, scenario = scenario_id)
+}
+
+/// A game in progress: its agent, current state and turn history
+#[derive(Clone)]
+pub struct Game {
+    span: Span,
+    id: String,
+    name: String,
+    scenario_id: String,
+    agent: Agent,
+    state: GameState,
+    history: Vec<TurnData>,
+}
+
+impl Game {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn scenario_id(&self) -> &str {
+        &self.scenario_id
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    pub fn history(&self) -> &[TurnData] {
+        &self.history
+    }
+
+    pub fn turn(&self, turn_number: u32) -> Option<&TurnData> {
+        self.history.get(turn_number as usize)
+    }
+
+    /// The newest turn, whose choices the player answers next
+    pub fn current_turn(&self) -> Option<&TurnData> {
+        self.history.last()
+    }
+
+    /// Discovered locations and the routes between them
+    pub fn world_map(&self) -> WorldMap {
+        self.state.world.discovered()
+    }
+
+    /// Token usage, speed and context use over the session
+    pub fn stats(&self) -> SessionStats {
+        SessionStats::of(&self.history)
+    }
+
+    /// Play one turn, streaming its messages to `emit`. The game only changes if
+    /// the turn completes, so a failed request can simply be retried.
+    pub async fn take_action<F>(&mut self, action: String, mut emit: F) -> Result<TurnData>
+    where
+        F: FnMut(AgentMessage) + Send,
+    {
+        let span = info_span!(parent: &self.span, "turn", number = self.history.len());
+        span.in_scope(|| info!(action = %redact(&action), "Player action"));
+        let mut agent = self.agent.clone();
+        let mut state = self.state.clone();
+        let mut completed = None;
+        agent
+            .process_action(action, &mut state, &self.history, |message| {
+                if let AgentMessage::TurnComplete(turn) = &message {
+                    completed = Some(turn.clone());
+                }
+                emit(message);
+            })
+            .instrument(span)
+            .await?;
+        let turn = completed.ok_or("The turn ended without a story")?;
+
+        self.agent = agent;
+        self.state = state;
+        self.history.push(turn.clone());
+        Ok(turn)
+    }
+
+    /// Definitions of the game tools enabled in this game's scenario
+    pub fn tool_definitions(&self) -> Vec<Tool> {
+        self.agent.tools().definitions()
+    }
+
+    /// Run one of the game's tools directly on the current state, outside any turn
+    pub fn run_tool(&mut self, name: &str, arguments: &Value) -> std::result::Result<String, ToolError> {
+        self.agent.tools().execute(name, arguments, &mut self.state)
+    }
+
+    /// Render the exact messages the next action would send to the model
+    pub async fn preview_prompt(&self, action: &str) -> Result<Vec<ChatMessage>> {
+        self.agent.preview_messages(action, &self.state, &self.history).await
+    }
+
+    /// Everything needed to resume this game later
+    pub fn save_file(&self) -> SaveFile {
+        SaveFile {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            scenario_id: self.scenario_id.clone(),
+            last_played: now_timestamp(),
+            history: self.history.clone(),
+            state: self.state.clone(),
+            agent: self.agent.session(),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+//! Game engine shared by the desktop app, the terminal client and the servers.
+//! [`Engine`] starts, loads and saves games; [`Game`] plays them one turn at a
+//! time; [`SessionManager`] hosts many games at once for the servers.
+
+pub mod agent;
+pub mod cassette;
+pub mod character;
+pub mod clock;
+pub mod context;
+pub mod engine;
+pub mod logging;
+pub mod lorebook;
+pub mod manager;
+pub mod memory;
+pub mod npc;
+pub mod ollama;
+pub mod pipeline;
+pub mod prompts;
+pub mod scenario;
+pub mod scripting;
+pub mod session;
+pub mod state;
+pub mod tools;
+pub mod usage;
+pub mod variables;
+pub mod world;
+
+pub use engine::{Engine, EngineConfig, Game};
+pub use manager::{SessionError, SessionManager};
@@ -0,0 +1,80 @@
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+use tracing::Level;
+use tracing_appender::rolling::{Builder, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt as format, EnvFilter};
+
+pub use tracing_appender::non_blocking::WorkerGuard;
+
+/// Environment variable holding the log filter in `tracing` directive syntax,
+/// e.g. `warn,luup_core=debug`
+pub const LOG_ENV: &str = "LUUP_LOG";
+
+/// Target whose debug level reveals prompt and response content in the logs
+pub const CONTENT_TARGET: &str = "luup_core::content";
+
+/// Daily log files kept before the oldest is deleted
+const MAX_LOG_FILES: usize = 7;
+
+/// Where and how much a program logs
+#[derive(Debug, Clone)]
+pub struct LogConfig {
+    /// Directory of the daily log files; none are written when unset
+    pub log_dir: Option<PathBuf>,
+    /// Log files are named `<file_name>.<date>.log`
+    pub file_name: &'static str,
+    /// Also log to stderr
+    pub stderr: bool,
+    /// Filter used when `LUUP_LOG` isn't set
+    pub default_filter: &'static str,
+}
+
+/// Install the global subscriber. Keep the returned guard until the program exits,
+/// or the last lines may not reach the log file.
+pub fn init(config: LogConfig) -> Result<Option<WorkerGuard>, Box<dyn Error + Send + Sync>> {
+    let filter = EnvFilter::try_from_env(LOG_ENV).or_else(|_| EnvFilter::try_new(config.default_filter))?;
+
+    let (file, guard) = match &config.log_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)?;
+            let appender = Builder::new()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(config.file_name)
+                .filename_suffix("log")
+                .max_log_files(MAX_LOG_FILES)
+                .build(dir)?;
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            (Some(format::layer().with_writer(writer).with_ansi(false)), Some(guard))
+        }
+        None => (None, None),
+    };
+    let stderr = config.stderr.then(|| format::layer().with_writer(std::io::stderr));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(file)
+        .with(stderr)
+        .try_init()?;
+    Ok(guard)
+}
+
+/// Prompt or response text for a log line. Only its length is shown unless debug
+/// logging is enabled for [`CONTENT_TARGET`].
+pub fn redact(text: &str) -> Redacted<'_> {
+    Redacted(text)
+}
+
+pub struct Redacted<'a>(&'a str);
+
+impl fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if tracing::enabled!(target: CONTENT_TARGET, Level::DEBUG) {
+            write!(f, "{:?}", self.0)
+        } else {
+            write!(f, "<{} chars>", self.0.chars().count())
+        }
+    }
+}
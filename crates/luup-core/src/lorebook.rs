@@ -0,0 +1,208 @@
+use crate::context::estimate_tokens;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+
+/// Where a triggered entry is placed in the request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InsertionPosition {
+    /// In a system message right after the system prompt
+    System,
+    /// In the player's message, before the action
+    #[default]
+    BeforeAction,
+    /// In the player's message, after the action
+    AfterAction,
+}
+
+/// A piece of world info injected only when its triggers appear
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoreEntry {
+    pub title: String,
+    pub content: String,
+    /// Case-insensitive whole words or phrases that trigger the entry
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Regular expressions that trigger the entry
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Higher priority entries win when the token budget runs out
+    #[serde(default)]
+    pub priority: i32,
+    /// Tokens the entry costs; estimated from the content when omitted
+    #[serde(default)]
+    pub token_cost: Option<usize>,
+    #[serde(default)]
+    pub position: InsertionPosition,
+}
+
+/// Contents of a scenario's lorebook file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LorebookFile {
+    /// How many past turns are scanned for triggers, besides the action
+    pub scan_depth: usize,
+    /// Most tokens of world info added to one request
+    pub token_budget: usize,
+    pub entries: Vec<LoreEntry>,
+}
+
+impl Default for LorebookFile {
+    fn default() -> Self {
+        Self {
+            scan_depth: 2,
+            token_budget: 600,
+            entries: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CompiledEntry {
+    entry: LoreEntry,
+    triggers: Vec<Regex>,
+}
+
+/// Lorebook with its triggers compiled
+#[derive(Debug, Clone, Default)]
+pub struct Lorebook {
+    pub scan_depth: usize,
+    token_budget: usize,
+    entries: Vec<CompiledEntry>,
+}
+
+impl Lorebook {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let file: LorebookFile = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::compile(file)
+    }
+
+    pub fn compile(file: LorebookFile) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut entries = Vec::new();
+        for entry in file.entries {
+            let keywords = entry
+                .keywords
+                .iter()
+                .map(|k| format!(r"\b{}\b", regex::escape(k.trim())));
+            let triggers = keywords
+                .chain(entry.patterns.iter().cloned())
+                .map(|pattern| {
+                    RegexBuilder::new(&pattern)
+                        .case_insensitive(true)
+                        .build()
+                        .map_err(|e| format!("Lore entry '{}': {}", entry.title, e))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            entries.push(CompiledEntry { entry, triggers });
+        }
+        Ok(Self {
+            scan_depth: file.scan_depth,
+            token_budget: file.token_budget,
+            entries,
+        })
+    }
+
+    /// Entries triggered by any of `texts`, highest priority first, within the token budget
+    pub fn select(&self, texts: &[&str]) -> Vec<&LoreEntry> {
+        let mut triggered: Vec<&LoreEntry> = self
+            .entries
+            .iter()
+            .filter(|e| e.triggers.iter().any(|t| texts.iter().any(|text| t.is_match(text))))
+            .map(|e| &e.entry)
+            .collect();
+        triggered.sort_by_key(|e| std::cmp::Reverse(e.priority));
+
+        let mut spent = 0;
+        triggered
+            .into_iter()
+            .filter(|entry| {
+                let cost = entry.token_cost.unwrap_or_else(|| estimate_tokens(&entry.content));
+                if spent + cost > self.token_budget {
+                    return false;
+                }
+                spent += cost;
+                true
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(title: &str, keywords: &[&str], priority: i32, token_cost: usize) -> LoreEntry {
+        LoreEntry {
+            title: title.to_string(),
+            content: format!("About {}", title),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            patterns: Vec::new(),
+            priority,
+            token_cost: Some(token_cost),
+            position: InsertionPosition::default(),
+        }
+    }
+
+    fn lorebook(token_budget: usize, entries: Vec<LoreEntry>) -> Lorebook {
+        Lorebook::compile(LorebookFile {
+            scan_depth: 2,
+            token_budget,
+            entries,
+        })
+        .unwrap()
+    }
+
+    fn titles(entries: Vec<&LoreEntry>) -> Vec<&str> {
+        entries.iter().map(|e| e.title.as_str()).collect()
+    }
+
+    #[test]
+    fn keywords_match_whole_words_in_any_case() {
+        let book = lorebook(100, vec![entry("Guild", &["thieves' guild"], 0, 10), entry("Rat", &["rat"], 0, 10)]);
+        assert_eq!(titles(book.select(&["I ask about the Thieves' Guild"])), ["Guild"]);
+        assert_eq!(titles(book.select(&["A pirate ship", "Keep the RAT away"])), ["Rat"]);
+        assert!(book.select(&["Nothing here"]).is_empty());
+    }
+
+    #[test]
+    fn patterns_trigger_entries_too() {
+        let mut tower = entry("Tower", &[], 0, 10);
+        tower.patterns = vec![r"tower(s)? of \w+".to_string()];
+        let book = lorebook(100, vec![tower]);
+        assert_eq!(titles(book.select(&["The towers of Ash rise"])), ["Tower"]);
+    }
+
+    #[test]
+    fn higher_priority_entries_come_first() {
+        let book = lorebook(
+            100,
+            vec![entry("Low", &["key"], 1, 10), entry("High", &["key"], 5, 10), entry("Mid", &["key"], 3, 10)],
+        );
+        assert_eq!(titles(book.select(&["the key"])), ["High", "Mid", "Low"]);
+    }
+
+    #[test]
+    fn entries_over_the_budget_are_dropped_lowest_priority_first() {
+        let book = lorebook(
+            50,
+            vec![entry("Big", &["key"], 1, 40), entry("First", &["key"], 9, 30), entry("Small", &["key"], 0, 20)],
+        );
+        // "Big" no longer fits after "First", but the cheaper "Small" still does
+        assert_eq!(titles(book.select(&["the key"])), ["First", "Small"]);
+    }
+
+    #[test]
+    fn invalid_patterns_fail_to_compile() {
+        let mut broken = entry("Broken", &[], 0, 10);
+        broken.patterns = vec!["(unclosed".to_string()];
+        let error = Lorebook::compile(LorebookFile {
+            entries: vec![broken],
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("Broken"), "{}", error);
+    }
+}
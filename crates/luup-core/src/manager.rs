@@ -0,0 +1,222 @@
+use crate::agent::AgentMessage;
+use crate::character::CharacterSheet;
+use crate::engine::{Engine, Game};
+use crate::session;
+use crate::state::TurnData;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::sync::broadcast;
+use tracing::{error, warn};
+
+/// Why a session operation failed
+#[derive(Debug)]
+pub enum SessionError {
+    /// No game is loaded or saved under this id
+    NotFound(String),
+    /// A turn or tool call is already running in this session
+    Busy(String),
+    Failed(Box<dyn Error + Send + Sync>),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::NotFound(id) => write!(f, "Session '{}' not found", id),
+            SessionError::Busy(id) => write!(f, "Session '{}' is already playing a turn", id),
+            SessionError::Failed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for SessionError {}
+
+impl From<Box<dyn Error + Send + Sync>> for SessionError {
+    fn from(e: Box<dyn Error + Send + Sync>) -> Self {
+        SessionError::Failed(e)
+    }
+}
+
+type Result<T> = std::result::Result<T, SessionError>;
+
+/// A stream message from one session's turn
+#[derive(Debug, Clone)]
+pub struct SessionEvent {
+    pub session_id: String,
+    pub message: AgentMessage,
+}
+
+/// The games a server is hosting, by session id. Every change is saved, and sessions
+/// share their id with their save, so a session from before a restart is picked up
+/// again from its save. Cheap to clone; clones share the same sessions.
+#[derive(Clone)]
+pub struct SessionManager {
+    inner: Arc<Sessions>,
+}
+
+struct Sessions {
+    engine: RwLock<Engine>,
+    games: Mutex<HashMap<String, Game>>,
+    /// Sessions with a turn or tool call in progress
+    busy: Mutex<HashSet<String>>,
+    events: broadcast::Sender<SessionEvent>,
+}
+
+impl SessionManager {
+    pub fn new(engine: Engine) -> Self {
+        let (events, _) = broadcast::channel(1024);
+        Self {
+            inner: Arc::new(Sessions {
+                engine: RwLock::new(engine),
+                games: Mutex::new(HashMap::new()),
+                busy: Mutex::new(HashSet::new()),
+                events,
+            }),
+        }
+    }
+
+    pub fn engine(&self) -> Engine {
+        self.inner.engine.read().unwrap().clone()
+    }
+
+    /// Replace the engine; games started or loaded from now on use it
+    pub fn set_engine(&self, engine: Engine) {
+        *self.inner.engine.write().unwrap() = engine;
+    }
+
+    /// Every session's stream messages from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionEvent> {
+        self.inner.events.subscribe()
+    }
+
+    /// Ids of the sessions in memory
+    pub fn session_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.inner.games.lock().unwrap().keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Start and save a game; see [`Engine::new_game`]
+    pub async fn new_game(&self, scenario_id: Option<&str>, character: Option<CharacterSheet>) -> Result<Game> {
+        let game = self.engine().new_game(scenario_id, character).await?;
+        self.store(game.clone()).await?;
+        Ok(game)
+    }
+
+    /// Resume a saved game as a session. Only a missing save is `NotFound`; a save that
+    /// can't be read or resumed is `Failed`.
+    pub async fn load_game(&self, save_id: &str) -> Result<Game> {
+        let engine = self.engine();
+        if !session::save_exists(&engine.saves_dir()?, save_id).await {
+            return Err(SessionError::NotFound(save_id.to_string()));
+        }
+        let game = engine.load_game(save_id).await.map_err(|e| {
+            warn!(save_id, error = %e, "Couldn't load save");
+            SessionError::Failed(e)
+        })?;
+        self.inner.games.lock().unwrap().insert(game.id().to_string(), game.clone());
+        Ok(game)
+    }
+
+    /// A copy of a session's game, loading it from its save if needed
+    pub async fn game(&self, session_id: &str) -> Result<Game> {
+        if let Some(game) = self.inner.games.lock().unwrap().get(session_id) {
+            return Ok(game.clone());
+        }
+        self.load_game(session_id).await
+    }
+
+    /// Play a turn, broadcasting its messages to subscribers. The turn runs to
+    /// completion and is saved even if the caller stops waiting for it.
+    pub async fn take_action(&self, session_id: &str, action: String) -> Result<TurnData> {
+        let guard = BusyGuard::acquire(self, session_id)?;
+        let mut game = self.game(session_id).await?;
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let events = manager.inner.events.clone();
+            let session_id = guard.session_id.clone();
+            let turn = game
+                .take_action(action, |message| {
+                    // Nobody listening is fine; the turn is still saved
+                    let _ = events.send(SessionEvent {
+                        session_id: session_id.clone(),
+                        message,
+                    });
+                })
+                .await?;
+            // The turn was played; a failed save shouldn't throw it away
+            if let Err(e) = manager.store(game).await {
+                error!(error = %e, "Autosave failed");
+            }
+            drop(guard);
+            Ok(turn)
+        })
+        .await
+        .map_err(|e| SessionError::Failed(e.into()))?
+    }
+
+    /// Run one of the game's tools directly on a session's state and save the result
+    pub async fn run_tool(&self, session_id: &str, name: &str, arguments: &Value) -> Result<String> {
+        let _guard = BusyGuard::acquire(self, session_id)?;
+        let mut game = self.game(session_id).await?;
+        let output = game.run_tool(name, arguments).map_err(|e| SessionError::Failed(e.into()))?;
+        self.store(game).await?;
+        Ok(output)
+    }
+
+    /// Make `game` current for its session and write it to its save file
+    async fn store(&self, game: Game) -> Result<()> {
+        let saved = self.engine().save(&game).await;
+        self.inner.games.lock().unwrap().insert(game.id().to_string(), game);
+        Ok(saved?)
+    }
+}
+
+/// Marks a session busy for as long as it lives
+struct BusyGuard {
+    manager: SessionManager,
+    session_id: String,
+}
+
+impl BusyGuard {
+    fn acquire(manager: &SessionManager, session_id: &str) -> Result<Self> {
+        if !manager.inner.busy.lock().unwrap().insert(session_id.to_string()) {
+            return Err(SessionError::Busy(session_id.to_string()));
+        }
+        Ok(Self {
+            manager: manager.clone(),
+            session_id: session_id.to_string(),
+        })
+    }
+}
+
+impl Drop for BusyGuard {
+    fn drop(&mut self) {
+        self.manager.inner.busy.lock().unwrap().remove(&self.session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::EngineConfig;
+
+    #[tokio::test]
+    async fn only_missing_saves_are_not_found() {
+        let data_dir = std::env::temp_dir().join(format!("luup-manager-{}", session::new_session_id("test")));
+        let engine = Engine::new(EngineConfig {
+            data_dir: Some(data_dir.clone()),
+            ..Default::default()
+        });
+        let saves = engine.saves_dir().unwrap();
+        std::fs::create_dir_all(&saves).unwrap();
+        std::fs::write(saves.join("broken.json"), "{ not json").unwrap();
+        let manager = SessionManager::new(engine);
+
+        assert!(matches!(manager.load_game("missing").await, Err(SessionError::NotFound(_))));
+        assert!(matches!(manager.load_game("broken").await, Err(SessionError::Failed(_))));
+        std::fs::remove_dir_all(&data_dir).unwrap();
+    }
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+/// Longest chunk indexed as a single memory
+const MAX_CHUNK_CHARS: usize = 800;
+
+/// Memories less similar to the query than this are never recalled
+const MIN_SIMILARITY: f32 = 0.35;
+
+/// A piece of a past turn with its embedding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryChunk {
+    pub turn_number: u32,
+    pub text: String,
+    pub embedding: Vec<f32>,
+}
+
+/// Vector store of past turns, saved with the session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryStore {
+    /// Embedding model the vectors came from; vectors from different models can't be compared
+    pub model: String,
+    pub chunks: Vec<MemoryChunk>,
+}
+
+impl MemoryStore {
+    pub fn add(&mut self, model: &str, turn_number: u32, texts: Vec<String>, embeddings: Vec<Vec<f32>>) {
+        if self.model != model {
+            // Switching embedding models invalidates everything indexed so far
+            self.chunks.clear();
+            self.model = model.to_string();
+        }
+        self.chunks.extend(texts.into_iter().zip(embeddings).map(|(text, embedding)| MemoryChunk {
+            turn_number,
+            text,
+            embedding,
+        }));
+    }
+
+    /// The `limit` memories most similar to `query`, only from turns before `before_turn`
+    pub fn search(&self, model: &str, query: &[f32], limit: usize, before_turn: u32) -> Vec<&MemoryChunk> {
+        if self.model != model {
+            return Vec::new();
+        }
+        let mut scored: Vec<(f32, &MemoryChunk)> = self
+            .chunks
+            .iter()
+            .filter(|c| c.turn_number < before_turn)
+            .map(|c| (cosine_similarity(query, &c.embedding), c))
+            .filter(|(score, _)| *score >= MIN_SIMILARITY)
+            .collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.into_iter().take(limit).map(|(_, c)| c).collect()
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Split a turn into paragraph-aligned chunks of at most `MAX_CHUNK_CHARS` characters;
+/// longer paragraphs are cut after a sentence, or mid-sentence when they have to be.
+/// The numbered choices are left out; they are options, not events.
+pub fn chunk_turn(action: &str, story_text: &str) -> Vec<String> {
+    let player = format!("Player: {}", action.trim());
+    let paragraphs = story_text
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .filter(|p| !["1.", "2.", "3."].iter().any(|n| p.starts_with(n)));
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+    for piece in std::iter::once(player.as_str()).chain(paragraphs).flat_map(pieces) {
+        let chars = piece.chars().count();
+        if !current.is_empty() && current_chars + chars + 1 > MAX_CHUNK_CHARS {
+            chunks.push(std::mem::take(&mut current));
+            current_chars = 0;
+        }
+        if !current.is_empty() {
+            current.push('\n');
+            current_chars += 1;
+        }
+        current.push_str(piece);
+        current_chars += chars;
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// `text` cut into pieces of at most `MAX_CHUNK_CHARS` characters, preferably after a
+/// sentence in the second half of a piece, else at a space, else anywhere
+fn pieces(mut text: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    while let Some((limit, _)) = text.char_indices().nth(MAX_CHUNK_CHARS) {
+        let head = &text[..limit];
+        let min = head.len() / 2;
+        let cut = head
+            .rfind(['.', '!', '?'])
+            .map(|i| i + 1)
+            .filter(|&i| i > min)
+            .or_else(|| head.rfind(' ').filter(|&i| i > 0))
+            .unwrap_or(limit);
+        pieces.push(text[..cut].trim_end());
+        text = text[cut..].trim_start();
+    }
+    if !text.is_empty() {
+        pieces.push(text);
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn longest(chunks: &[String]) -> usize {
+        chunks.iter().map(|c| c.chars().count()).max().unwrap_or(0)
+    }
+
+    #[test]
+    fn keeps_short_paragraphs_together_and_skips_choices() {
+        let chunks = chunk_turn("Open the door", "The door creaks.\n\nA draft.\n\n1. Enter\n2. Leave");
+        assert_eq!(chunks, ["Player: Open the door\nThe door creaks.\nA draft."]);
+    }
+
+    #[test]
+    fn splits_a_long_paragraph_after_sentences() {
+        let sentence = "The rain keeps falling on the old stone bridge. ";
+        let paragraph = sentence.repeat(2000 / sentence.len() + 1);
+        let chunks = chunk_turn("Wait", &paragraph);
+
+        assert!(chunks.len() >= 3, "{} chunks", chunks.len());
+        assert!(longest(&chunks) <= MAX_CHUNK_CHARS);
+        assert!(chunks.iter().skip(1).all(|c| c.starts_with("The rain") && c.ends_with("bridge.")));
+        let words = |text: &str| text.split_whitespace().count();
+        assert_eq!(chunks.iter().map(|c| words(c)).sum::<usize>(), words(&paragraph) + 2);
+    }
+
+    #[test]
+    fn counts_characters_not_bytes() {
+        // Two bytes per character and no place to break
+        let paragraph = "é".repeat(2000);
+        let chunks = chunk_turn("Listen", &paragraph);
+
+        assert!(longest(&chunks) <= MAX_CHUNK_CHARS);
+        assert!(chunks.iter().any(|c| c.chars().count() == MAX_CHUNK_CHARS));
+        assert_eq!(chunks.concat().matches('é').count(), 2000);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::ollama::{ChatMessage, ModelOptions, OllamaClient, StreamChunk, Tool, ToolFunction};
+use crate::tools::parameters_for;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::error::Error;
+use tokio_stream::StreamExt;
+
+/// Name of the tool the narrator uses to let an NPC speak
+pub const TALK_TO_NPC: &str = "talk_to_npc";
+
+/// Messages an NPC remembers; older conversations are forgotten
+const MAX_NPC_MEMORY: usize = 20;
+
+/// An NPC declared in `scenario.json`; `persona` is a path relative to the pack directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NpcDef {
+    pub name: String,
+    pub persona: String,
+    /// Model voicing this NPC; the app's model when unset
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub options: ModelOptions,
+}
+
+/// An NPC loaded from a scenario pack, with its persona text
+#[derive(Debug, Clone)]
+pub struct Npc {
+    pub def: NpcDef,
+    pub persona: String,
+}
+
+/// Arguments of `talk_to_npc`
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct TalkToNpcArgs {
+    /// Name of the character the player is talking to
+    pub npc: String,
+    /// What the player says or asks, in their own words
+    pub message: String,
+}
+
+/// An NPC's side of its conversations with the player, saved with the session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NpcMemory {
+    pub messages: Vec<ChatMessage>,
+}
+
+impl NpcMemory {
+    pub fn record(&mut self, said: String, reply: String) {
+        self.messages.push(ChatMessage::new("user", said));
+        self.messages.push(ChatMessage::new("assistant", reply));
+        let excess = self.messages.len().saturating_sub(MAX_NPC_MEMORY);
+        self.messages.drain(..excess);
+    }
+}
+
+/// Sub-agent voicing one NPC with its own prompt and model
+#[derive(Clone)]
+pub struct NpcAgent {
+    pub name: String,
+    system_prompt: String,
+    client: OllamaClient,
+}
+
+impl NpcAgent {
+    pub fn new(name: String, system_prompt: String, client: OllamaClient) -> Self {
+        Self {
+            name,
+            system_prompt,
+            client,
+        }
+    }
+
+    /// Stream the NPC's reply to `line`, calling `on_chunk` with each piece of it
+    pub async fn reply<F>(
+        &self,
+        memory: &NpcMemory,
+        line: String,
+        mut on_chunk: F,
+    ) -> Result<String, Box<dyn Error + Send + Sync>>
+    where
+        F: FnMut(String) + Send,
+    {
+        let mut messages = vec![ChatMessage::new("system", self.system_prompt.clone())];
+        messages.extend(memory.messages.iter().cloned());
+        messages.push(ChatMessage::new("user", line));
+
+        let mut stream = self.client.chat_stream(messages, Vec::new()).await?;
+        let mut reply = String::new();
+        while let Some(chunk) = stream.next().await {
+            match chunk? {
+                StreamChunk::TextChunk(content) => {
+                    reply.push_str(&content);
+                    on_chunk(content);
+                }
+                StreamChunk::Done(_) => break,
+                // NPCs get no tools, and their reasoning isn't shown
+                _ => {}
+            }
+        }
+        let reply = reply.trim().to_string();
+        if reply.is_empty() {
+            return Err(format!("{} had nothing to say", self.name).into());
+        }
+        Ok(reply)
+    }
+}
+
+/// Definition of `talk_to_npc`, limited to the given NPCs
+pub fn tool_definition(npcs: &[NpcAgent]) -> Tool {
+    let names: Vec<&str> = npcs.iter().map(|n| n.name.as_str()).collect();
+    let mut parameters = parameters_for::<TalkToNpcArgs>();
+    if let Some(npc) = parameters.properties.get_mut("npc").and_then(|p| p.as_object_mut()) {
+        npc.insert("enum".to_string(), json!(names));
+    }
+    Tool {
+        tool_type: "function".to_string(),
+        function: ToolFunction {
+            name: TALK_TO_NPC.to_string(),
+            description: format!(
+                "Let a character answer the player in their own voice. Use it whenever the player speaks to {}",
+                names.join(", ")
+            ),
+            parameters,
+        },
+    }
+}
@@ -0,0 +1,701 @@
+use crate::cassette::{ByteStream, Cassette};
+use crate::logging::redact;
+use crate::usage::Usage;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio_stream::Stream;
+use futures::stream::StreamExt;
+use tracing::{debug, trace, warn};
+use ts_rs::TS;
+
+/// Chunks of a streamed chat reply
+pub type ChunkStream = Pin<Box<dyn Stream<Item = Result<StreamChunk, Box<dyn Error + Send + Sync>>> + Send>>;
+
+/// Ollama host and port used until the player configures one
+pub const DEFAULT_URL: &str = "192.168.0.100:11434";
+
+/// Longest wait between two retries
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Ollama client for communicating with the local LLM
+#[derive(Clone)]
+pub struct OllamaClient {
+    base_url: String,
+    model: String,
+    embedding_model: String,
+    options: ModelOptions,
+    http_client: reqwest::Client,
+    /// Records or replays this client's traffic
+    cassette: Option<Cassette>,
+    policy: RequestPolicy,
+}
+
+/// Timeouts and retries for requests to Ollama, in milliseconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RequestPolicy {
+    /// Longest wait for a connection to the server
+    pub connect_timeout_ms: u64,
+    /// Longest wait from sending a request to the first streamed chunk, which includes
+    /// loading the model; for replies that aren't streamed, to the whole reply
+    pub first_token_timeout_ms: u64,
+    /// Longest gap between two chunks of a streamed reply
+    pub idle_timeout_ms: u64,
+    /// Further attempts when the server can't be reached, is busy or times out, as long
+    /// as nothing has been streamed yet
+    pub max_retries: u32,
+    /// Wait before the first retry; it doubles for each one after
+    pub initial_backoff_ms: u64,
+}
+
+impl Default for RequestPolicy {
+    fn default() -> Self {
+        Self {
+            connect_timeout_ms: 5_000,
+            first_token_timeout_ms: 180_000,
+            idle_timeout_ms: 60_000,
+            max_retries: 3,
+            initial_backoff_ms: 500,
+        }
+    }
+}
+
+/// Request settings from a JSON file such as `{"first_token_timeout_ms": 300000}`
+pub fn load_policy(path: &Path) -> Result<RequestPolicy, Box<dyn Error + Send + Sync>> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// What went wrong talking to Ollama, so the UI can suggest a fix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum ErrorKind {
+    /// The server can't be reached, or stayed busy through every retry
+    Unreachable,
+    /// The model isn't installed on the server
+    ModelMissing,
+    /// The prompt doesn't fit the model's context window
+    ContextOverflow,
+    /// No reply within the first-token timeout, or the reply stalled
+    Timeout,
+    /// Any other error status
+    Server,
+}
+
+impl ErrorKind {
+    /// The kind of `error`, if it came from a request to Ollama
+    pub fn of(error: &(dyn Error + 'static)) -> Option<Self> {
+        error.downcast_ref::<OllamaError>().map(|e| e.kind)
+    }
+}
+
+/// A failed request to Ollama
+#[derive(Debug)]
+pub struct OllamaError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl OllamaError {
+    fn new(kind: ErrorKind, message: String) -> Self {
+        Self { kind, message }
+    }
+
+    /// An error status, classified by its code and Ollama's message
+    fn from_status(status: StatusCode, body: &[u8]) -> Self {
+        let body = String::from_utf8_lossy(body);
+        let message = serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|v| v["error"].as_str().map(str::to_string))
+            .unwrap_or_else(|| body.trim().to_string());
+        let lower = message.to_lowercase();
+        let kind = if status == StatusCode::NOT_FOUND || (lower.contains("model") && lower.contains("not found")) {
+            ErrorKind::ModelMissing
+        } else if lower.contains("context") && ["length", "window", "exceed"].iter().any(|w| lower.contains(w)) {
+            ErrorKind::ContextOverflow
+        } else if matches!(status.as_u16(), 429 | 502 | 503 | 504) {
+            // Busy, e.g. while another model loads
+            ErrorKind::Unreachable
+        } else {
+            ErrorKind::Server
+        };
+        Self::new(kind, format!("Ollama request failed: {}: {}", status, message))
+    }
+
+    /// Whether trying again may help
+    fn is_retryable(&self) -> bool {
+        matches!(self.kind, ErrorKind::Unreachable | ErrorKind::Timeout)
+    }
+}
+
+impl fmt::Display for OllamaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for OllamaError {}
+
+/// Model parameters sent with every request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelOptions {
+    /// Context window size in tokens; Ollama's own default applies when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Most tokens generated per reply
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_predict: Option<i32>,
+}
+
+impl ModelOptions {
+    /// These options, with any set in `overrides` taking precedence
+    pub fn merged(&self, overrides: &ModelOptions) -> Self {
+        Self {
+            num_ctx: overrides.num_ctx.or(self.num_ctx),
+            temperature: overrides.temperature.or(self.temperature),
+            top_p: overrides.top_p.or(self.top_p),
+            num_predict: overrides.num_predict.or(self.num_predict),
+        }
+    }
+}
+
+/// Tool definition matching Ollama's native format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tool {
+    #[serde(rename = "type")]
+    pub tool_type: String,
+    pub function: ToolFunction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolFunction {
+    pub name: String,
+    pub description: String,
+    pub parameters: ToolParameters,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolParameters {
+    #[serde(rename = "type")]
+    pub param_type: String,
+    pub required: Vec<String>,
+    pub properties: serde_json::Map<String, Value>,
+}
+
+/// Chat message for conversation history
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+    /// Native tool calls made by the assistant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub tool_calls: Option<Vec<Value>>,
+    /// Name of the tool a `tool` role message is answering
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub tool_name: Option<String>,
+    /// Reasoning streamed separately by thinking models; never sent back
+    #[serde(default, skip_serializing)]
+    #[ts(skip)]
+    pub thinking: Option<String>,
+}
+
+impl ChatMessage {
+    pub fn new(role: &str, content: String) -> Self {
+        Self {
+            role: role.to_string(),
+            content,
+            tool_calls: None,
+            tool_name: None,
+            thinking: None,
+        }
+    }
+
+    /// Result of a tool call, fed back to the model
+    pub fn tool_result(tool_name: &str, content: String) -> Self {
+        Self {
+            tool_name: Some(tool_name.to_string()),
+            ..Self::new("tool", content)
+        }
+    }
+}
+
+/// Request to Ollama chat endpoint
+#[derive(Debug, Serialize)]
+struct OllamaRequest {
+    model: String,
+    messages: Vec<ChatMessage>,
+    stream: bool,
+    tools: Option<Vec<Tool>>,
+    options: ModelOptions,
+}
+
+/// Request to Ollama embed endpoint
+#[derive(Debug, Serialize)]
+struct EmbedRequest<'a> {
+    model: &'a str,
+    input: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+/// Streamed response chunk from Ollama
+#[derive(Debug, Deserialize)]
+pub struct OllamaStreamChunk {
+    pub model: String,
+    pub created_at: String,
+    pub message: Option<ChatMessage>,
+    pub done: bool,
+    #[serde(default)]
+    pub done_reason: Option<String>,
+    /// Counters sent with the final chunk; durations are in nanoseconds
+    #[serde(default)]
+    pub prompt_eval_count: u32,
+    #[serde(default)]
+    pub eval_count: u32,
+    #[serde(default)]
+    pub total_duration: u64,
+    #[serde(default)]
+    pub load_duration: u64,
+    #[serde(default)]
+    pub prompt_eval_duration: u64,
+    #[serde(default)]
+    pub eval_duration: u64,
+}
+
+impl OllamaStreamChunk {
+    pub fn usage(&self) -> Usage {
+        Usage::of_reply(
+            self.prompt_eval_count,
+            self.eval_count,
+            [self.total_duration, self.load_duration, self.prompt_eval_duration, self.eval_duration],
+        )
+    }
+}
+
+/// Types of chunks we can receive from the stream
+#[derive(Debug, Clone)]
+pub enum StreamChunk {
+    TextChunk(String),
+    ReasoningChunk(String),
+    ToolCall { name: String, arguments: Value },
+    /// The reply is complete, with what it cost
+    Done(Usage),
+}
+
+impl Default for OllamaClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OllamaClient {
+    /// Create a new Ollama client for `DEFAULT_URL`
+    pub fn new() -> Self {
+        Self::with_url(DEFAULT_URL.to_string())
+    }
+
+    /// Create a new Ollama client with custom base URL
+    pub fn with_url(base_url: String) -> Self {
+        Self {
+            base_url: format!("http://{}", base_url),
+            model: "qwen3:8b".to_string(),
+            embedding_model: "nomic-embed-text".to_string(),
+            options: ModelOptions::default(),
+            http_client: http_client(&RequestPolicy::default()),
+            cassette: None,
+            policy: RequestPolicy::default(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn embedding_model(&self) -> &str {
+        &self.embedding_model
+    }
+
+    /// Copy of this client using another model and/or extra options
+    pub fn with_model(&self, model: Option<&str>, options: &ModelOptions) -> Self {
+        Self {
+            model: model.unwrap_or(&self.model).to_string(),
+            options: self.options.merged(options),
+            ..self.clone()
+        }
+    }
+
+    /// Record every request and reply to a cassette, or replay one instead of calling Ollama
+    pub fn set_cassette(&mut self, cassette: Option<Cassette>) {
+        self.cassette = cassette;
+    }
+
+    /// Timeouts and retries for every request
+    pub fn set_request_policy(&mut self, policy: RequestPolicy) {
+        self.http_client = http_client(&policy);
+        self.policy = policy;
+    }
+
+    /// Context window requested from Ollama
+    pub fn set_context_length(&mut self, tokens: usize) {
+        self.options.num_ctx = Some(tokens);
+    }
+
+    /// Send a chat request without tools and wait for the complete reply
+    pub async fn chat(&self, messages: Vec<ChatMessage>) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let request = OllamaRequest {
+            model: self.model.clone(),
+            messages,
+            stream: false,
+            tools: None,
+            options: self.options.clone(),
+        };
+
+        let body = self
+            .with_retries(|| async {
+                let (status, body) = self.post("/api/chat", &request).await?;
+                Ok(read_body(ensure_success(status, body).await?).await?)
+            })
+            .await?;
+
+        // A non-streamed reply has the same shape as the final stream chunk
+        let reply: OllamaStreamChunk = serde_json::from_slice(&body)?;
+        let content = reply.message.map(|m| m.content).unwrap_or_default();
+        // Reasoning models may put their thinking inline before the answer
+        let answer = match content.find("</think>") {
+            Some(end) => &content[end + "</think>".len()..],
+            None => &content,
+        };
+        Ok(answer.trim().to_string())
+    }
+
+    /// Embed each input with the embedding model, in order
+    pub async fn embed(&self, input: Vec<String>) -> Result<Vec<Vec<f32>>, Box<dyn Error + Send + Sync>> {
+        let count = input.len();
+        let request = EmbedRequest {
+            model: &self.embedding_model,
+            input,
+        };
+        let body = self
+            .with_retries(|| async {
+                let (status, body) = self.post("/api/embed", &request).await?;
+                Ok(read_body(ensure_success(status, body).await?).await?)
+            })
+            .await?;
+
+        let reply: EmbedResponse = serde_json::from_slice(&body)?;
+        if reply.embeddings.len() != count {
+            return Err(format!("Expected {} embeddings, got {}", count, reply.embeddings.len()).into());
+        }
+        Ok(reply.embeddings)
+    }
+
+    /// Send a chat request with tools and return a stream of chunks. The request is
+    /// retried until the first chunk arrives; after that a stall ends the stream with
+    /// a timeout error.
+    pub async fn chat_stream(
+        &self,
+        messages: Vec<ChatMessage>,
+        tools: Vec<Tool>,
+    ) -> Result<ChunkStream, Box<dyn Error + Send + Sync>> {
+        let request = OllamaRequest {
+            model: self.model.clone(),
+            messages,
+            stream: true,
+            tools: if tools.is_empty() { None } else { Some(tools) },
+            options: self.options.clone(),
+        };
+
+        debug!(
+            url = %self.base_url,
+            model = %self.model,
+            messages = request.messages.len(),
+            "Sending chat request"
+        );
+
+        self.with_retries(|| self.open_stream(&request)).await
+    }
+
+    /// Start a streamed reply and wait for its first chunk. A reply that fails before
+    /// then is returned as the error, so it can be retried like a failed request.
+    async fn open_stream(&self, request: &OllamaRequest) -> Result<ChunkStream, Box<dyn Error + Send + Sync>> {
+        let (status, body) = self.post("/api/chat", request).await?;
+        let mut chunks: ChunkStream = Box::pin(Self::parse_stream(ensure_success(status, body).await?));
+        let first = match chunks.next().await {
+            Some(Err(e)) => return Err(e),
+            first => first,
+        };
+        let idle = Duration::from_millis(self.policy.idle_timeout_ms);
+        Ok(Box::pin(futures::stream::iter(first).chain(idle_timeout(chunks, idle))))
+    }
+
+    /// Run `attempt` until it succeeds, each time within the first-token timeout.
+    /// Failures that may pass, such as a busy or unreachable server, are retried
+    /// with exponential backoff.
+    async fn with_retries<T, F, Fut>(&self, mut attempt: F) -> Result<T, Box<dyn Error + Send + Sync>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Box<dyn Error + Send + Sync>>>,
+    {
+        let timeout = Duration::from_millis(self.policy.first_token_timeout_ms);
+        let mut backoff = Duration::from_millis(self.policy.initial_backoff_ms);
+        let mut retries = 0;
+        loop {
+            let result = match tokio::time::timeout(timeout, attempt()).await {
+                Ok(result) => result,
+                Err(_) => Err(OllamaError::new(
+                    ErrorKind::Timeout,
+                    format!("Ollama didn't answer within {} s", timeout.as_secs_f32()),
+                )
+                .into()),
+            };
+            let retryable = match &result {
+                Err(e) => e.downcast_ref::<OllamaError>().is_some_and(OllamaError::is_retryable),
+                Ok(_) => false,
+            };
+            if !retryable || retries >= self.policy.max_retries {
+                return result;
+            }
+            retries += 1;
+            if let Err(e) = &result {
+                warn!(retry = retries, delay_ms = backoff.as_millis() as u64, error = %e, "Retrying Ollama request");
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    /// POST `request` to an Ollama endpoint, returning the status and the raw body.
+    /// Goes through the cassette when there is one.
+    async fn post<T: Serialize>(
+        &self,
+        endpoint: &str,
+        request: &T,
+    ) -> Result<(StatusCode, ByteStream), Box<dyn Error + Send + Sync>> {
+        if let Some(cassette) = self.cassette.as_ref().filter(|c| c.is_replaying()) {
+            let (status, body) = cassette.play(endpoint, &serde_json::to_value(request)?)?;
+            return Ok((StatusCode::from_u16(status)?, body));
+        }
+
+        let sent = self
+            .http_client
+            .post(format!("{}{}", self.base_url, endpoint))
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| {
+                let kind = if e.is_timeout() && !e.is_connect() {
+                    ErrorKind::Timeout
+                } else {
+                    ErrorKind::Unreachable
+                };
+                // reqwest's own message leaves out the cause, e.g. a refused connection
+                let cause = std::iter::successors(e.source(), |&s| s.source()).last().map(|s| s.to_string());
+                OllamaError::new(
+                    kind,
+                    format!("Can't reach Ollama at {}: {}", self.base_url, cause.unwrap_or_else(|| e.to_string())),
+                )
+            });
+        let response = match sent {
+            Ok(response) => response,
+            Err(e) => {
+                if let Some(cassette) = &self.cassette {
+                    cassette.record_failure(endpoint, serde_json::to_value(request)?, &e);
+                }
+                return Err(e.into());
+            }
+        };
+        let status = response.status();
+        let body: ByteStream = Box::pin(response.bytes_stream().map(|chunk| chunk.map_err(std::io::Error::other)));
+        Ok(match &self.cassette {
+            Some(cassette) => {
+                let body = cassette.record_reply(endpoint, serde_json::to_value(request)?, status.as_u16(), body);
+                (status, body)
+            }
+            None => (status, body),
+        })
+    }
+
+    /// Parse NDJSON stream into typed chunks. A network chunk may hold several
+    /// lines, or end partway through one; a last line without a newline still counts.
+    fn parse_stream<S, E>(stream: S) -> impl Stream<Item = Result<StreamChunk, Box<dyn Error + Send + Sync>>>
+    where
+        S: Stream<Item = Result<bytes::Bytes, E>> + Unpin,
+        E: Error + Send + Sync + 'static,
+    {
+        futures::stream::unfold(
+            (Some(stream), Vec::new(), VecDeque::new()),
+            |(mut stream, mut buffer, mut pending)| async move {
+                loop {
+                    // Drain chunks queued from a line that produced several
+                    if let Some(chunk) = pending.pop_front() {
+                        return Some((Ok(chunk), (stream, buffer, pending)));
+                    }
+
+                    // Every complete line is handled before reading more
+                    let line = match buffer.iter().position(|&b| b == b'\n') {
+                        Some(newline_pos) => buffer.drain(..=newline_pos).collect::<Vec<_>>(),
+                        None => match stream.as_mut()?.next().await {
+                            Some(Ok(bytes)) => {
+                                buffer.extend_from_slice(&bytes);
+                                continue;
+                            }
+                            Some(Err(e)) => {
+                                let error = OllamaError::new(ErrorKind::Unreachable, format!("The reply broke off: {}", e));
+                                return Some((Err(error.into()), (stream, buffer, pending)));
+                            }
+                            // Stream ended; whatever is left is the last line
+                            None => {
+                                stream = None;
+                                std::mem::take(&mut buffer)
+                            }
+                        },
+                    };
+
+                    let line = String::from_utf8_lossy(&line);
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    if let Err(e) = Self::parse_line(&line, &mut pending) {
+                        return Some((Err(e), (stream, buffer, pending)));
+                    }
+                }
+            },
+        )
+    }
+
+    /// Queue the chunks carried by one NDJSON line
+    fn parse_line(line: &str, pending: &mut VecDeque<StreamChunk>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let chunk = serde_json::from_str::<OllamaStreamChunk>(line).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+        trace!(done = chunk.done, message = %redact(&format!("{:?}", chunk.message)), "Ollama chunk");
+        if chunk.done {
+            let usage = chunk.usage();
+            debug!(
+                prompt_tokens = usage.prompt_tokens,
+                completion_tokens = usage.completion_tokens,
+                total_ms = usage.total_ms,
+                "Ollama stream done"
+            );
+            pending.push_back(StreamChunk::Done(usage));
+            return Ok(());
+        }
+        let Some(message) = chunk.message else {
+            return Ok(());
+        };
+
+        // Thinking models stream their reasoning in its own field
+        if let Some(thinking) = message.thinking.filter(|t| !t.is_empty()) {
+            pending.push_back(StreamChunk::ReasoningChunk(thinking));
+        }
+
+        // Native tool calls arrive in their own field
+        if let Some(tool_calls) = &message.tool_calls {
+            let before = pending.len();
+            for call in tool_calls {
+                let function = call.get("function");
+                if let (Some(name), Some(args)) = (
+                    function.and_then(|f| f.get("name")).and_then(|n| n.as_str()),
+                    function.and_then(|f| f.get("arguments")),
+                ) {
+                    pending.push_back(StreamChunk::ToolCall {
+                        name: name.to_string(),
+                        arguments: args.clone(),
+                    });
+                }
+            }
+            if pending.len() > before {
+                return Ok(());
+            }
+        }
+
+        // Check if this is a tool call embedded in the content
+        if let Ok(content_json) = serde_json::from_str::<Value>(&message.content) {
+            let first_call = content_json
+                .get("tool_calls")
+                .and_then(|calls| calls.as_array())
+                .and_then(|calls| calls.first())
+                .and_then(|call| call.get("function"));
+            if let (Some(name), Some(args)) = (
+                first_call.and_then(|f| f.get("name")).and_then(|n| n.as_str()),
+                first_call.and_then(|f| f.get("arguments")),
+            ) {
+                pending.push_back(StreamChunk::ToolCall {
+                    name: name.to_string(),
+                    arguments: args.clone(),
+                });
+                return Ok(());
+            }
+        }
+
+        // Regular text content
+        if !message.content.is_empty() {
+            // Check if it looks like reasoning (starts with "thinking:" or similar)
+            if message.content.starts_with("<think>") || message.content.contains("reasoning:") {
+                pending.push_back(StreamChunk::ReasoningChunk(message.content));
+            } else {
+                pending.push_back(StreamChunk::TextChunk(message.content));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn http_client(policy: &RequestPolicy) -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(Duration::from_millis(policy.connect_timeout_ms))
+        .build()
+        .unwrap_or_default()
+}
+
+/// The body of a successful reply, or the error Ollama gave
+async fn ensure_success(status: StatusCode, body: ByteStream) -> Result<ByteStream, Box<dyn Error + Send + Sync>> {
+    if status.is_success() {
+        return Ok(body);
+    }
+    let body = read_body(body).await.unwrap_or_default();
+    Err(OllamaError::from_status(status, &body).into())
+}
+
+/// End `chunks` with a timeout error once the next chunk takes longer than `idle`
+fn idle_timeout(chunks: ChunkStream, idle: Duration) -> ChunkStream {
+    Box::pin(futures::stream::unfold(Some(chunks), move |chunks| async move {
+        let mut chunks = chunks?;
+        match tokio::time::timeout(idle, chunks.next()).await {
+            Ok(chunk) => chunk.map(|chunk| (chunk, Some(chunks))),
+            Err(_) => {
+                let message = format!("Ollama stopped sending for {} s", idle.as_secs_f32());
+                Some((Err(OllamaError::new(ErrorKind::Timeout, message).into()), None))
+            }
+        }
+    }))
+}
+
+/// Read a whole response body
+async fn read_body(mut body: ByteStream) -> Result<Vec<u8>, std::io::Error> {
+    let mut content = Vec::new();
+    while let Some(chunk) = body.next().await {
+        content.extend_from_slice(&chunk?);
+    }
+    Ok(content)
+}
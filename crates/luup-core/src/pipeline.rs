@@ -0,0 +1,41 @@
+use crate::ollama::ModelOptions;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+use ts_rs::TS;
+
+/// A model pass within a turn when the pipeline is enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum PipelineStage {
+    /// Decides outcomes and changes the game state through tools; writes no prose
+    Arbiter,
+    /// Writes the story from the arbiter's ruling, without tools
+    Narrator,
+}
+
+/// Model settings for one stage
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StageConfig {
+    /// Model for this stage; the app's model when unset
+    pub model: Option<String>,
+    /// Options layered over the app's, e.g. a low temperature for the arbiter
+    pub options: ModelOptions,
+}
+
+/// Splits each turn into a rules pass and a narration pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineConfig {
+    pub enabled: bool,
+    pub arbiter: StageConfig,
+    pub narrator: StageConfig,
+}
+
+/// Pipeline settings from a JSON file such as
+/// `{"enabled": true, "arbiter": {"options": {"temperature": 0.2}}}`
+pub fn load_pipeline(path: &Path) -> Result<PipelineConfig, Box<dyn Error + Send + Sync>> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
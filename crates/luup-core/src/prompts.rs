@@ -0,0 +1,202 @@
+use crate::character::CharacterSheet;
+use crate::lorebook::LoreEntry;
+use crate::scenario::ScenarioSummary;
+use crate::state::{GameState, TurnData};
+use minijinja::{Environment, UndefinedBehavior};
+use serde::Serialize;
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::debug;
+
+const SYSTEM_TEMPLATE: &str = "system.j2";
+const USER_TEMPLATE: &str = "user.j2";
+const SUMMARY_TEMPLATE: &str = "summary.j2";
+const ARBITER_TEMPLATE: &str = "arbiter.j2";
+const RULING_TEMPLATE: &str = "ruling.j2";
+const NPC_TEMPLATE: &str = "npc.j2";
+
+/// Templates compiled into the app, used when nothing overrides them
+const DEFAULT_SYSTEM: &str = include_str!("../prompts/system.j2");
+const DEFAULT_USER: &str = include_str!("../prompts/user.j2");
+const DEFAULT_SUMMARY: &str = include_str!("../prompts/summary.j2");
+const DEFAULT_ARBITER: &str = include_str!("../prompts/arbiter.j2");
+const DEFAULT_RULING: &str = include_str!("../prompts/ruling.j2");
+const DEFAULT_NPC: &str = include_str!("../prompts/npc.j2");
+
+/// Model settings visible to templates
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptSettings {
+    pub model: String,
+    pub ollama_url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolInfo {
+    pub name: String,
+    pub description: String,
+}
+
+/// Context for `system.j2` and `arbiter.j2`
+#[derive(Debug, Serialize)]
+pub struct SystemPromptContext<'a> {
+    pub persona: &'a str,
+    pub lore: Option<&'a str>,
+    pub tools: Vec<ToolInfo>,
+    pub scenario: ScenarioSummary,
+    pub character: &'a CharacterSheet,
+    pub state: &'a GameState,
+    /// Whether a rules arbiter handles the tools, leaving the narrator to write prose
+    pub pipeline: bool,
+    pub settings: PromptSettings,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DueEvent {
+    pub at: String,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecalledMemory {
+    pub turn_number: u32,
+    pub text: String,
+}
+
+/// Context for `user.j2`
+#[derive(Debug, Serialize)]
+pub struct UserPromptContext<'a> {
+    pub action: &'a str,
+    pub state: &'a GameState,
+    /// Preformatted values, so templates don't have to rebuild them
+    pub time: String,
+    pub exits: Vec<String>,
+    pub variables: String,
+    pub due_events: Vec<DueEvent>,
+    /// Past turns retrieved from long-term memory as relevant to the action
+    pub memories: Vec<RecalledMemory>,
+    /// Triggered lorebook entries placed before / after the action
+    pub world_info_before: Vec<&'a LoreEntry>,
+    pub world_info_after: Vec<&'a LoreEntry>,
+    pub recent_turns: &'a [TurnData],
+    pub settings: PromptSettings,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TranscriptEntry {
+    pub speaker: String,
+    pub content: String,
+}
+
+/// Context for `npc.j2`
+#[derive(Debug, Serialize)]
+pub struct NpcPromptContext<'a> {
+    pub name: &'a str,
+    pub persona: &'a str,
+    pub lore: Option<&'a str>,
+    pub scenario: ScenarioSummary,
+    /// The player character the NPC is talking to
+    pub character: &'a CharacterSheet,
+    pub settings: PromptSettings,
+}
+
+/// A tool call made by the arbiter
+#[derive(Debug, Serialize)]
+pub struct RulingChange {
+    pub name: String,
+    /// The call's arguments as JSON
+    pub arguments: String,
+    pub output: String,
+}
+
+/// Context for `ruling.j2`
+#[derive(Debug, Serialize)]
+pub struct RulingPromptContext<'a> {
+    pub ruling: &'a str,
+    pub changes: &'a [RulingChange],
+    /// Game state after the arbiter's tool calls
+    pub state: &'a GameState,
+    pub time: String,
+    pub settings: PromptSettings,
+}
+
+/// Context for `summary.j2`
+#[derive(Debug, Serialize)]
+pub struct SummaryPromptContext<'a> {
+    pub previous_summary: &'a str,
+    pub transcript: Vec<TranscriptEntry>,
+    pub settings: PromptSettings,
+}
+
+/// Jinja templates for the system prompt, the per-turn user message, the
+/// story summary request, the arbiter's prompts and NPC personas
+#[derive(Clone)]
+pub struct PromptTemplates {
+    env: Arc<Environment<'static>>,
+}
+
+impl Default for PromptTemplates {
+    fn default() -> Self {
+        Self::load(&[]).expect("built-in prompt templates must compile")
+    }
+}
+
+impl PromptTemplates {
+    /// Built-in templates, overridden by any `system.j2` / `user.j2` /
+    /// `summary.j2` / `arbiter.j2` / `ruling.j2` / `npc.j2` found in `dirs`. Later directories win.
+    pub fn load(dirs: &[PathBuf]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut sources = [
+            (SYSTEM_TEMPLATE, DEFAULT_SYSTEM.to_string()),
+            (USER_TEMPLATE, DEFAULT_USER.to_string()),
+            (SUMMARY_TEMPLATE, DEFAULT_SUMMARY.to_string()),
+            (ARBITER_TEMPLATE, DEFAULT_ARBITER.to_string()),
+            (RULING_TEMPLATE, DEFAULT_RULING.to_string()),
+            (NPC_TEMPLATE, DEFAULT_NPC.to_string()),
+        ];
+        for dir in dirs {
+            for (name, source) in sources.iter_mut() {
+                let path = dir.join(*name);
+                if path.is_file() {
+                    debug!(path = %path.display(), "Using prompt template");
+                    *source = std::fs::read_to_string(&path)?;
+                }
+            }
+        }
+
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        // Typos in a template should fail loudly rather than render as blanks
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        for (name, source) in sources {
+            env.add_template_owned(name, source)
+                .map_err(|e| format!("{}: {}", name, e))?;
+        }
+
+        Ok(Self { env: Arc::new(env) })
+    }
+
+    pub fn render_system(&self, context: &SystemPromptContext) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(self.env.get_template(SYSTEM_TEMPLATE)?.render(context)?)
+    }
+
+    pub fn render_user(&self, context: &UserPromptContext) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(self.env.get_template(USER_TEMPLATE)?.render(context)?)
+    }
+
+    pub fn render_summary(&self, context: &SummaryPromptContext) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(self.env.get_template(SUMMARY_TEMPLATE)?.render(context)?)
+    }
+
+    pub fn render_arbiter(&self, context: &SystemPromptContext) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(self.env.get_template(ARBITER_TEMPLATE)?.render(context)?)
+    }
+
+    pub fn render_ruling(&self, context: &RulingPromptContext) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(self.env.get_template(RULING_TEMPLATE)?.render(context)?)
+    }
+
+    pub fn render_npc(&self, context: &NpcPromptContext) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(self.env.get_template(NPC_TEMPLATE)?.render(context)?)
+    }
+}
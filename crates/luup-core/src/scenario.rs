@@ -0,0 +1,184 @@
+use crate::character::CharacterTemplate;
+use crate::lorebook::Lorebook;
+use crate::npc::{Npc, NpcDef};
+use crate::state::GameState;
+use crate::variables::{VarDecl, VariableStore};
+use crate::world::Location;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use ts_rs::TS;
+
+/// File every scenario pack directory must contain
+const MANIFEST_FILE: &str = "scenario.json";
+
+/// The first turn shown before the player acts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpeningScene {
+    pub story_text: String,
+    pub choices: Vec<String>,
+}
+
+/// Contents of `scenario.json`.
+/// `persona`, `lore`, `lorebook` and NPC personas are paths relative to the pack directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioManifest {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub genre: String,
+    #[serde(default)]
+    pub persona: Option<String>,
+    #[serde(default)]
+    pub lore: Option<String>,
+    /// World info entries injected when their keywords come up
+    #[serde(default)]
+    pub lorebook: Option<String>,
+    pub opening: OpeningScene,
+    /// Options offered during character creation
+    #[serde(default)]
+    pub character: CharacterTemplate,
+    #[serde(default)]
+    pub initial_state: GameState,
+    /// Declared story variables, seeded with their initial values
+    #[serde(default)]
+    pub variables: Vec<VarDecl>,
+    /// Names of the tools the model may use; omit to enable all of them
+    #[serde(default)]
+    pub enabled_tools: Option<Vec<String>>,
+    /// Characters voiced by their own sub-agents through `talk_to_npc`
+    #[serde(default)]
+    pub npcs: Vec<NpcDef>,
+}
+
+/// Short description shown in the scenario picker, with its character creation options
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ScenarioSummary {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub genre: String,
+    pub character: CharacterTemplate,
+}
+
+/// A loaded scenario pack
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    pub manifest: ScenarioManifest,
+    pub dir: PathBuf,
+    /// Persona prompt that opens the system prompt, if the pack provides one
+    pub persona: Option<String>,
+    pub lore: Option<String>,
+    pub lorebook: Lorebook,
+    pub npcs: Vec<Npc>,
+}
+
+impl Scenario {
+    /// Load a scenario pack from its directory
+    pub fn load(dir: &Path) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let manifest: ScenarioManifest = serde_json::from_str(&std::fs::read_to_string(&manifest_path)?)
+            .map_err(|e| format!("{}: {}", manifest_path.display(), e))?;
+
+        let read_optional = |file: &Option<String>| -> Result<Option<String>, std::io::Error> {
+            file.as_ref()
+                .map(|f| std::fs::read_to_string(dir.join(f)))
+                .transpose()
+        };
+        let persona = read_optional(&manifest.persona)?;
+        let lore = read_optional(&manifest.lore)?;
+        let lorebook = match &manifest.lorebook {
+            Some(file) => Lorebook::load(&dir.join(file))?,
+            None => Lorebook::default(),
+        };
+        let npcs = manifest
+            .npcs
+            .iter()
+            .map(|def| {
+                let persona = std::fs::read_to_string(dir.join(&def.persona))
+                    .map_err(|e| format!("NPC '{}': {}", def.name, e))?;
+                Ok(Npc { def: def.clone(), persona })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Self {
+            manifest,
+            dir: dir.to_path_buf(),
+            persona,
+            lore,
+            lorebook,
+            npcs,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.manifest.id
+    }
+
+    pub fn summary(&self) -> ScenarioSummary {
+        ScenarioSummary {
+            id: self.manifest.id.clone(),
+            name: self.manifest.name.clone(),
+            description: self.manifest.description.clone(),
+            genre: self.manifest.genre.clone(),
+            character: self.manifest.character.clone(),
+        }
+    }
+
+    /// Directory holding the pack's `*.rhai` tool scripts
+    pub fn tools_dir(&self) -> PathBuf {
+        self.dir.join("tools")
+    }
+
+    /// Directory holding the pack's `system.j2` / `user.j2` prompt overrides
+    pub fn prompts_dir(&self) -> PathBuf {
+        self.dir.join("prompts")
+    }
+
+    /// Game state at turn 0
+    pub fn initial_state(&self) -> GameState {
+        let mut state = self.manifest.initial_state.clone();
+        if !self.manifest.variables.is_empty() {
+            state.variables = VariableStore::with_schema(self.manifest.variables.clone());
+        }
+        // The starting location must exist for move_to to have somewhere to start from
+        if !state.location.is_empty() && state.world.get(&state.location).is_none() {
+            state.world.locations.push(Location {
+                name: state.location.clone(),
+                description: String::new(),
+                discovered: true,
+            });
+        }
+        state
+    }
+}
+
+/// Find every scenario pack under the given roots.
+/// Packs in later roots override packs with the same id in earlier ones.
+pub fn discover_scenarios(roots: &[PathBuf]) -> Vec<Scenario> {
+    let mut scenarios: Vec<Scenario> = Vec::new();
+    for root in roots {
+        let Ok(entries) = std::fs::read_dir(root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            if !dir.join(MANIFEST_FILE).is_file() {
+                continue;
+            }
+            match Scenario::load(&dir) {
+                Ok(scenario) => {
+                    scenarios.retain(|s| s.id() != scenario.id());
+                    scenarios.push(scenario);
+                }
+                Err(e) => warn!(path = %dir.display(), error = %e, "Skipping scenario"),
+            }
+        }
+    }
+    scenarios.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+    scenarios
+}
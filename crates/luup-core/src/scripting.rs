@@ -0,0 +1,181 @@
+use crate::ollama::ToolParameters;
+use crate::state::GameState;
+use crate::tools::DynTool;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, Scope, AST};
+use serde_json::Value;
+use std::error::Error;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info};
+
+/// Upper bound on work a single tool call may do before it is aborted
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// Random source behind the scripts' `roll`, shared by every script of a game
+pub type Dice = Arc<Mutex<StdRng>>;
+
+/// Dice seeded with `seed` for repeatable rolls, or from the OS when unset
+pub fn dice(seed: Option<u64>) -> Dice {
+    Arc::new(Mutex::new(match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }))
+}
+
+/// A tool defined by a Rhai script in a scenario pack.
+///
+/// The script must define two functions:
+/// - `tool()` returning a map with `name`, `description`, `parameters`
+///   (property name to JSON-schema map) and optionally `required`
+/// - `run(args)`, called with the game state bound to `this`; its return
+///   value is reported to the model
+#[derive(Clone)]
+pub struct ScriptedTool {
+    name: String,
+    description: String,
+    parameters: ToolParameters,
+    engine: Arc<Engine>,
+    ast: Arc<AST>,
+}
+
+/// Metadata returned by a script's `tool()` function
+#[derive(serde::Deserialize)]
+struct ScriptManifest {
+    name: String,
+    description: String,
+    #[serde(default)]
+    parameters: serde_json::Map<String, Value>,
+    #[serde(default)]
+    required: Vec<String>,
+}
+
+/// Engine with resource limits and without `eval`, so scripts stay sandboxed
+fn sandboxed_engine(dice: Dice) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(16 * 1024)
+        .set_max_array_size(1_000)
+        .set_max_map_size(1_000)
+        .disable_symbol("eval");
+    engine.on_print(|text| info!(output = text, "Script printed"));
+    engine.on_debug(|text, _, _| debug!(output = text, "Script debug"));
+    // Dice for minigames: `roll(20)` returns 1..=20
+    engine.register_fn("roll", move |sides: i64| -> Result<i64, Box<EvalAltResult>> {
+        if sides < 1 {
+            return Err(format!("roll needs at least one side, got {}", sides).into());
+        }
+        let mut rng = dice.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(rng.gen_range(1..=sides))
+    });
+    engine
+}
+
+impl ScriptedTool {
+    /// Compile a script and read its tool definition; `roll` draws from `dice`
+    pub fn from_source(source: &str, dice: Dice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let engine = sandboxed_engine(dice);
+        let ast = engine.compile(source)?;
+        let manifest: Dynamic = engine.call_fn(&mut Scope::new(), &ast, "tool", ())?;
+        let manifest: ScriptManifest = rhai::serde::from_dynamic(&manifest)?;
+
+        Ok(Self {
+            name: manifest.name,
+            description: manifest.description,
+            parameters: ToolParameters {
+                param_type: "object".to_string(),
+                required: manifest.required,
+                properties: manifest.parameters,
+            },
+            engine: Arc::new(engine),
+            ast: Arc::new(ast),
+        })
+    }
+
+    pub fn from_file(path: &Path, dice: Dice) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let source = std::fs::read_to_string(path)?;
+        Self::from_source(&source, dice).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+}
+
+impl DynTool for ScriptedTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        self.parameters.clone()
+    }
+
+    fn execute(&self, arguments: &Value, state: &mut GameState) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let args = rhai::serde::to_dynamic(arguments)?;
+        let mut this = rhai::serde::to_dynamic(&*state)?;
+
+        let result: Dynamic = self.engine.call_fn_with_options(
+            CallFnOptions::new().bind_this_ptr(&mut this),
+            &mut Scope::new(),
+            &self.ast,
+            "run",
+            (args,),
+        )?;
+
+        // Only commit the state once the script has finished without errors
+        *state = rhai::serde::from_dynamic(&this)
+            .map_err(|e| format!("Script '{}' left the game state invalid: {}", self.name, e))?;
+        Ok(if result.is_unit() {
+            "Done".to_string()
+        } else {
+            result.to_string()
+        })
+    }
+}
+
+/// Load every `*.rhai` tool script in a directory
+pub fn load_script_tools(dir: &Path, dice: &Dice) -> Result<Vec<ScriptedTool>, Box<dyn Error + Send + Sync>> {
+    let mut tools = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "rhai") {
+            tools.push(ScriptedTool::from_file(&path, dice.clone())?);
+        }
+    }
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(tools)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DICE_TOOL: &str = r#"
+        fn tool() { #{ name: "roll_dice", description: "Roll a die", parameters: #{ sides: #{ type: "integer" } } } }
+        fn run(args) { roll(args.sides) }
+    "#;
+
+    fn rolls(seed: u64) -> Vec<String> {
+        let tool = ScriptedTool::from_source(DICE_TOOL, dice(Some(seed))).unwrap();
+        let mut state = GameState::default();
+        (0..8).map(|_| tool.execute(&serde_json::json!({"sides": 20}), &mut state).unwrap()).collect()
+    }
+
+    #[test]
+    fn seeded_dice_repeat_their_rolls() {
+        assert_eq!(rolls(7), rolls(7));
+        assert!(rolls(7).iter().all(|r| (1..=20).contains(&r.parse::<i64>().unwrap())));
+    }
+
+    #[test]
+    fn rolling_a_die_without_sides_is_a_script_error() {
+        let tool = ScriptedTool::from_source(DICE_TOOL, dice(Some(7))).unwrap();
+        let error = tool.execute(&serde_json::json!({"sides": 0}), &mut GameState::default()).unwrap_err();
+        assert!(error.to_string().contains("at least one side"), "{}", error);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::agent::AgentSession;
+use crate::state::{GameState, TurnData};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use ts_rs::TS;
+
+/// Everything needed to resume a game, stored as `<saves dir>/<id>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveFile {
+    pub id: String,
+    pub name: String,
+    pub scenario_id: String,
+    pub last_played: String,
+    pub history: Vec<TurnData>,
+    pub state: GameState,
+    /// Conversation, story summary and long-term memory
+    #[serde(default)]
+    pub agent: AgentSession,
+}
+
+/// Entry in the list of saved games
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SaveGame {
+    pub id: String,
+    pub name: String,
+    pub last_played: String,
+    pub turn_count: u32,
+}
+
+impl SaveFile {
+    pub fn summary(&self) -> SaveGame {
+        SaveGame {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            last_played: self.last_played.clone(),
+            turn_count: self.history.len() as u32,
+        }
+    }
+}
+
+/// Id for a new session: scenario, start time and a random suffix, so games started
+/// in the same second still get their own save
+pub fn new_session_id(scenario_id: &str) -> String {
+    format!(
+        "{}-{}-{:06x}",
+        scenario_id,
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        rand::random::<u32>() & 0xff_ffff
+    )
+}
+
+/// Timestamp stored in `last_played`
+pub fn now_timestamp() -> String {
+    chrono::Local::now().format("%Y-%m-%d %H:%M").to_string()
+}
+
+fn save_path(dir: &Path, id: &str) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
+    // Ids come from the frontend, so keep them from escaping the saves directory
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid save id '{}'", id).into());
+    }
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+/// Write a save, replacing any previous one with the same id
+pub async fn write_save(dir: &Path, save: &SaveFile) -> Result<(), Box<dyn Error + Send + Sync>> {
+    tokio::fs::create_dir_all(dir).await?;
+    let path = save_path(dir, &save.id)?;
+    // Write next to the target first so a crash never leaves a half-written save
+    let tmp = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp, serde_json::to_vec(save)?).await?;
+    tokio::fs::rename(&tmp, &path).await?;
+    Ok(())
+}
+
+/// Whether `dir` already holds a save with this id
+pub async fn save_exists(dir: &Path, id: &str) -> bool {
+    match save_path(dir, id) {
+        Ok(path) => tokio::fs::try_exists(path).await.unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+pub async fn read_save(dir: &Path, id: &str) -> Result<SaveFile, Box<dyn Error + Send + Sync>> {
+    let path = save_path(dir, id)?;
+    let save = serde_json::from_str(&tokio::fs::read_to_string(&path).await?)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(save)
+}
+
+/// Every readable save in `dir`, most recently played first
+pub async fn list_saves(dir: &Path) -> Vec<SaveGame> {
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return Vec::new();
+    };
+    let mut saves = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let save = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<SaveFile>(&s).map_err(|e| e.to_string()));
+        match save {
+            Ok(save) => saves.push(save.summary()),
+            Err(e) => warn!(path = %path.display(), error = %e, "Skipping save"),
+        }
+    }
+    saves.sort_by(|a, b| b.last_played.cmp(&a.last_played));
+    saves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn games_started_together_get_distinct_valid_ids() {
+        let ids: Vec<String> = (0..20).map(|_| new_session_id("fantasy")).collect();
+        let mut unique = ids.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), ids.len());
+        assert!(ids.iter().all(|id| save_path(Path::new("saves"), id).is_ok()));
+    }
+
+    #[tokio::test]
+    async fn finds_existing_saves() {
+        let dir = std::env::temp_dir().join(format!("luup-saves-{}", new_session_id("test")));
+        let save = SaveFile {
+            id: new_session_id("fantasy"),
+            name: "Test".to_string(),
+            scenario_id: "fantasy".to_string(),
+            last_played: now_timestamp(),
+            history: Vec::new(),
+            state: GameState::default(),
+            agent: AgentSession::default(),
+        };
+        assert!(!save_exists(&dir, &save.id).await);
+        write_save(&dir, &save).await.unwrap();
+        assert!(save_exists(&dir, &save.id).await);
+        assert!(!save_exists(&dir, "../escape").await);
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
@@ -0,0 +1,51 @@
+use crate::character::CharacterSheet;
+use crate::clock::{take_due_events, GameClock, ScheduledEvent};
+use crate::usage::Usage;
+use crate::variables::VariableStore;
+use crate::world::WorldMap;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Game state shared by the agent, the Tauri commands and save files.
+/// Missing fields fall back to their defaults, so saves from older versions keep loading.
+/// The starting values for a new game come from its scenario pack.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+#[ts(export)]
+pub struct GameState {
+    pub character: CharacterSheet,
+    /// Older saves stored a plain time-of-day string under `time`
+    #[serde(alias = "time")]
+    pub clock: GameClock,
+    pub location: String,
+    pub outfit: String,
+    pub world: WorldMap,
+    /// Events waiting for the clock to reach them
+    pub scheduled_events: Vec<ScheduledEvent>,
+    /// Events the clock passed this turn, reported to the model on the next one
+    pub triggered_events: Vec<ScheduledEvent>,
+    pub variables: VariableStore,
+}
+
+impl GameState {
+    /// Advance the clock and collect any scheduled events it passes
+    pub fn advance_time(&mut self, minutes: u64) {
+        self.clock.advance(minutes);
+        let due = take_due_events(&mut self.scheduled_events, &self.clock);
+        self.triggered_events.extend(due);
+    }
+}
+
+/// One completed turn, as stored in the history and shown to the player
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TurnData {
+    pub turn_number: u32,
+    pub story_text: String,
+    pub choices: Vec<String>,
+    pub game_state: GameState,
+    /// What the turn's model requests cost; unset for the opening and older saves
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub usage: Option<Usage>,
+}
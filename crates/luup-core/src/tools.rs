@@ -0,0 +1,645 @@
+use crate::clock::ScheduledEvent;
+use crate::ollama::{Tool, ToolFunction, ToolParameters};
+use crate::state::GameState;
+use schemars::gen::SchemaSettings;
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+/// A tool the model can call to change the game state.
+/// The JSON schema sent to the model is derived from `Args`.
+pub trait GameTool: Send + Sync {
+    type Args: DeserializeOwned + JsonSchema;
+
+    fn name(&self) -> &str;
+    fn description(&self) -> &str;
+
+    /// Apply the tool and return a short result for the model
+    fn run(&self, args: Self::Args, state: &mut GameState) -> Result<String, Box<dyn Error + Send + Sync>>;
+}
+
+/// Object-safe view of a tool, working on raw JSON arguments
+pub trait DynTool: Send + Sync {
+    fn name(&self) -> &str;
+    fn description(&self) -> &str;
+    fn parameters(&self) -> ToolParameters;
+    fn execute(&self, arguments: &Value, state: &mut GameState) -> Result<String, Box<dyn Error + Send + Sync>>;
+}
+
+impl<T: GameTool> DynTool for T {
+    fn name(&self) -> &str {
+        GameTool::name(self)
+    }
+
+    fn description(&self) -> &str {
+        GameTool::description(self)
+    }
+
+    fn parameters(&self) -> ToolParameters {
+        parameters_for::<T::Args>()
+    }
+
+    fn execute(&self, arguments: &Value, state: &mut GameState) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let args = serde_json::from_value(arguments.clone())
+            .map_err(|e| format!("Invalid arguments for {}: {}", GameTool::name(self), e))?;
+        self.run(args, state)
+    }
+}
+
+/// Build the tool parameter schema for an arguments struct
+pub fn parameters_for<A: JsonSchema>() -> ToolParameters {
+    let schema = SchemaSettings::draft07()
+        .with(|s| {
+            s.inline_subschemas = true;
+            s.option_add_null_type = false;
+            s.meta_schema = None;
+        })
+        .into_generator()
+        .into_root_schema_for::<A>();
+    let schema = serde_json::to_value(schema).unwrap_or_default();
+
+    let required = schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| r.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+    let mut properties = schema
+        .get("properties")
+        .and_then(|p| p.as_object())
+        .cloned()
+        .unwrap_or_default();
+    // Small models get confused by JSON-schema details they don't need
+    for property in properties.values_mut() {
+        if let Some(obj) = property.as_object_mut() {
+            obj.remove("format");
+        }
+    }
+
+    ToolParameters {
+        param_type: "object".to_string(),
+        required,
+        properties,
+    }
+}
+
+/// One way in which tool-call arguments break the tool's schema
+#[derive(Debug, Clone, Serialize)]
+pub struct ArgumentViolation {
+    /// JSON pointer to the offending value, empty for the arguments object itself
+    pub path: String,
+    pub message: String,
+}
+
+/// Why a tool call could not be applied
+#[derive(Debug)]
+pub enum ToolError {
+    UnknownTool(String),
+    InvalidArguments(Vec<ArgumentViolation>),
+    Failed(String),
+}
+
+impl fmt::Display for ToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolError::UnknownTool(name) => write!(f, "Unknown tool: {}", name),
+            ToolError::InvalidArguments(violations) => {
+                let messages: Vec<String> = violations
+                    .iter()
+                    .map(|v| if v.path.is_empty() { v.message.clone() } else { format!("{}: {}", v.path, v.message) })
+                    .collect();
+                write!(f, "Invalid arguments: {}", messages.join("; "))
+            }
+            ToolError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for ToolError {}
+
+impl ToolError {
+    /// Structured tool result sent back to the model so it can correct the call
+    pub fn to_tool_result(&self, tool_name: &str) -> Value {
+        match self {
+            ToolError::UnknownTool(_) => json!({
+                "error": "unknown_tool",
+                "tool": tool_name,
+                "message": self.to_string(),
+            }),
+            ToolError::InvalidArguments(violations) => json!({
+                "error": "invalid_arguments",
+                "tool": tool_name,
+                "violations": violations,
+            }),
+            ToolError::Failed(message) => json!({
+                "error": "execution_failed",
+                "tool": tool_name,
+                "message": message,
+            }),
+        }
+    }
+}
+
+/// Check tool-call arguments against the tool's declared parameters.
+/// Arguments not declared in the schema are rejected.
+pub fn validate_arguments(parameters: &ToolParameters, arguments: &Value) -> Result<(), Vec<ArgumentViolation>> {
+    let schema = json!({
+        "type": parameters.param_type,
+        "required": parameters.required,
+        "properties": parameters.properties,
+        "additionalProperties": false,
+    });
+    let validator = jsonschema::validator_for(&schema).map_err(|e| {
+        vec![ArgumentViolation {
+            path: String::new(),
+            message: format!("Tool schema is invalid: {}", e),
+        }]
+    })?;
+
+    let violations: Vec<ArgumentViolation> = validator
+        .iter_errors(arguments)
+        .map(|e| ArgumentViolation {
+            path: e.instance_path.to_string(),
+            message: e.to_string(),
+        })
+        .collect();
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+/// The set of tools offered to the model, with dispatch by name
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<Arc<dyn DynTool>>,
+    disabled: HashSet<String>,
+}
+
+impl ToolRegistry {
+    /// Registry holding every built-in game tool
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        registry.register(AdvanceTime);
+        registry.register(ScheduleEvent);
+        registry.register(CreateLocation);
+        registry.register(ConnectLocations);
+        registry.register(MoveTo);
+        registry.register(SetOutfit);
+        registry.register(SetFlag);
+        registry.register(GetFlag);
+        registry.register(Increment);
+        registry
+    }
+
+    /// Add a tool, replacing any existing tool with the same name
+    pub fn register<T: DynTool + 'static>(&mut self, tool: T) {
+        self.tools.retain(|t| t.name() != tool.name());
+        self.tools.push(Arc::new(tool));
+    }
+
+    /// Add a scenario's tool unless one with the same name is already registered, so scripts
+    /// can't change the built-in rules. Returns whether the tool was added.
+    pub fn register_new<T: DynTool + 'static>(&mut self, tool: T) -> bool {
+        if self.tools.iter().any(|t| t.name() == tool.name()) {
+            return false;
+        }
+        self.tools.push(Arc::new(tool));
+        true
+    }
+
+    /// Restrict the registry to the named tools; `None` enables everything
+    pub fn set_enabled(&mut self, enabled: Option<&[String]>) {
+        self.disabled = match enabled {
+            Some(names) => self
+                .tools
+                .iter()
+                .map(|t| t.name().to_string())
+                .filter(|name| !names.contains(name))
+                .collect(),
+            None => HashSet::new(),
+        };
+    }
+
+    fn enabled(&self) -> impl Iterator<Item = &Arc<dyn DynTool>> {
+        self.tools.iter().filter(|t| !self.disabled.contains(t.name()))
+    }
+
+    /// Tool definitions in Ollama's format
+    pub fn definitions(&self) -> Vec<Tool> {
+        self.enabled()
+            .map(|t| Tool {
+                tool_type: "function".to_string(),
+                function: ToolFunction {
+                    name: t.name().to_string(),
+                    description: t.description().to_string(),
+                    parameters: t.parameters(),
+                },
+            })
+            .collect()
+    }
+
+    /// Validate the arguments and run an enabled tool by name
+    pub fn execute(&self, name: &str, arguments: &Value, state: &mut GameState) -> Result<String, ToolError> {
+        let tool = self
+            .enabled()
+            .find(|t| t.name() == name)
+            .ok_or_else(|| ToolError::UnknownTool(name.to_string()))?;
+        validate_arguments(&tool.parameters(), arguments).map_err(ToolError::InvalidArguments)?;
+        tool.execute(arguments, state).map_err(|e| match e.downcast::<ToolError>() {
+            Ok(error) => *error,
+            Err(e) => ToolError::Failed(e.to_string()),
+        })
+    }
+}
+
+/// A length of in-game time; the fields are added together
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct Duration {
+    /// Number of minutes
+    #[schemars(range(max = 525600))]
+    pub minutes: Option<u64>,
+    /// Number of hours
+    #[schemars(range(max = 8760))]
+    pub hours: Option<u64>,
+    /// Number of days
+    #[schemars(range(max = 365))]
+    pub days: Option<u64>,
+}
+
+impl Duration {
+    /// Saturates rather than overflowing, as the numbers come from the model
+    pub fn total_minutes(&self) -> u64 {
+        self.minutes
+            .unwrap_or(0)
+            .saturating_add(self.hours.unwrap_or(0).saturating_mul(60))
+            .saturating_add(self.days.unwrap_or(0).saturating_mul(24 * 60))
+    }
+}
+
+pub struct AdvanceTime;
+
+impl GameTool for AdvanceTime {
+    type Args = Duration;
+
+    fn name(&self) -> &str {
+        "advance_time"
+    }
+
+    fn description(&self) -> &str {
+        "Move the game clock forward. Durations in each field are added together"
+    }
+
+    fn run(&self, args: Duration, state: &mut GameState) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let minutes = args.total_minutes();
+        if minutes == 0 {
+            return Err("advance_time needs a positive 'minutes', 'hours' or 'days' argument".into());
+        }
+        state.advance_time(minutes);
+        Ok(format!("The time is now {}", state.clock))
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ScheduleEventArgs {
+    /// What happens when the event triggers
+    pub description: String,
+    /// Delay from now until the event triggers
+    #[serde(flatten)]
+    pub delay: Duration,
+}
+
+pub struct ScheduleEvent;
+
+impl GameTool for ScheduleEvent {
+    type Args = ScheduleEventArgs;
+
+    fn name(&self) -> &str {
+        "schedule_event"
+    }
+
+    fn description(&self) -> &str {
+        "Schedule a story event to trigger once the clock has advanced by the given delay"
+    }
+
+    fn run(&self, args: ScheduleEventArgs, state: &mut GameState) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let mut at = state.clock;
+        at.advance(args.delay.total_minutes());
+        state.scheduled_events.push(ScheduledEvent {
+            at,
+            description: args.description,
+        });
+        Ok(format!("Scheduled for {}", at))
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CreateLocationArgs {
+    /// The name of the new location
+    pub name: String,
+    /// A short description of the location
+    pub description: String,
+    /// An existing location this one connects to (defaults to the player's current location)
+    pub connected_to: Option<String>,
+    /// Minutes it takes to travel between the two locations
+    #[schemars(range(max = 1440))]
+    pub travel_minutes: Option<u32>,
+}
+
+pub struct CreateLocation;
+
+impl GameTool for CreateLocation {
+    type Args = CreateLocationArgs;
+
+    fn name(&self) -> &str {
+        "create_location"
+    }
+
+    fn description(&self) -> &str {
+        "Add a new place to the world map, connected to an existing location"
+    }
+
+    fn run(&self, args: CreateLocationArgs, state: &mut GameState) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let connected_to = args.connected_to.unwrap_or_else(|| state.location.clone());
+        // Places next to the player are visible to them right away
+        let discovered = connected_to.eq_ignore_ascii_case(&state.location);
+        state.world.add_location(
+            &args.name,
+            &args.description,
+            &connected_to,
+            args.travel_minutes.unwrap_or(10),
+            discovered,
+        )?;
+        Ok(format!("Created '{}', connected to '{}'", args.name, connected_to))
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ConnectLocationsArgs {
+    /// One existing location
+    pub from: String,
+    /// Another existing location
+    pub to: String,
+    /// Minutes it takes to travel between the two locations
+    #[schemars(range(max = 1440))]
+    pub travel_minutes: Option<u32>,
+}
+
+pub struct ConnectLocations;
+
+impl GameTool for ConnectLocations {
+    type Args = ConnectLocationsArgs;
+
+    fn name(&self) -> &str {
+        "connect_locations"
+    }
+
+    fn description(&self) -> &str {
+        "Add a path between two existing locations, such as a shortcut or a back door"
+    }
+
+    fn run(&self, args: ConnectLocationsArgs, state: &mut GameState) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let travel_minutes = args.travel_minutes.unwrap_or(10);
+        state.world.connect(&args.from, &args.to, travel_minutes)?;
+        Ok(format!("Connected '{}' and '{}' ({} minutes)", args.from, args.to, travel_minutes))
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct MoveToArgs {
+    /// The name of the destination
+    pub location: String,
+}
+
+pub struct MoveTo;
+
+impl GameTool for MoveTo {
+    type Args = MoveToArgs;
+
+    fn name(&self) -> &str {
+        "move_to"
+    }
+
+    fn description(&self) -> &str {
+        "Move the player to a location adjacent to their current one"
+    }
+
+    fn run(&self, args: MoveToArgs, state: &mut GameState) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let (name, travel_minutes) = state.world.travel(&state.location, &args.location)?;
+        state.location = name;
+        state.advance_time(travel_minutes as u64);
+        Ok(format!("Arrived at '{}' after {} minutes", state.location, travel_minutes))
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct SetOutfitArgs {
+    /// Description of the outfit or equipment
+    pub outfit: String,
+}
+
+pub struct SetOutfit;
+
+impl GameTool for SetOutfit {
+    type Args = SetOutfitArgs;
+
+    fn name(&self) -> &str {
+        "set_outfit"
+    }
+
+    fn description(&self) -> &str {
+        "Change the player's outfit or equipment"
+    }
+
+    fn run(&self, args: SetOutfitArgs, state: &mut GameState) -> Result<String, Box<dyn Error + Send + Sync>> {
+        state.outfit = args.outfit;
+        Ok(format!("Outfit is now: {}", state.outfit))
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct SetFlagArgs {
+    /// Name of the story variable, e.g. door_unlocked or gold
+    pub name: String,
+    /// The new value
+    #[schemars(schema_with = "scalar_schema")]
+    pub value: Value,
+}
+
+fn scalar_schema(_gen: &mut schemars::gen::SchemaGenerator) -> Schema {
+    SchemaObject {
+        instance_type: Some(SingleOrVec::Vec(vec![
+            InstanceType::Boolean,
+            InstanceType::Integer,
+            InstanceType::Number,
+            InstanceType::String,
+        ])),
+        ..Default::default()
+    }
+    .into()
+}
+
+pub struct SetFlag;
+
+impl GameTool for SetFlag {
+    type Args = SetFlagArgs;
+
+    fn name(&self) -> &str {
+        "set_flag"
+    }
+
+    fn description(&self) -> &str {
+        "Set a story variable such as a flag, counter or note"
+    }
+
+    fn run(&self, args: SetFlagArgs, state: &mut GameState) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let value = state.variables.set(&args.name, &args.value)?;
+        Ok(format!("{} = {}", args.name, value))
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct GetFlagArgs {
+    /// Name of the story variable, e.g. door_unlocked or gold
+    pub name: String,
+}
+
+pub struct GetFlag;
+
+impl GameTool for GetFlag {
+    type Args = GetFlagArgs;
+
+    fn name(&self) -> &str {
+        "get_flag"
+    }
+
+    fn description(&self) -> &str {
+        "Look up the current value of a story variable"
+    }
+
+    fn run(&self, args: GetFlagArgs, state: &mut GameState) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(match state.variables.get(&args.name) {
+            Some(value) => format!("{} = {}", args.name, value),
+            None => format!("{} is not set", args.name),
+        })
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct IncrementArgs {
+    /// Name of the story variable, e.g. door_unlocked or gold
+    pub name: String,
+    /// Amount to add (negative to subtract, defaults to 1)
+    pub amount: Option<f64>,
+}
+
+pub struct Increment;
+
+impl GameTool for Increment {
+    type Args = IncrementArgs;
+
+    fn name(&self) -> &str {
+        "increment"
+    }
+
+    fn description(&self) -> &str {
+        "Add an amount to a numeric story variable"
+    }
+
+    fn run(&self, args: IncrementArgs, state: &mut GameState) -> Result<String, Box<dyn Error + Send + Sync>> {
+        // Keep whole numbers integral so integer variables accept them
+        let amount = match args.amount {
+            Some(x) if !x.is_finite() || (x.fract() == 0.0 && x.abs() >= i64::MAX as f64) => {
+                return Err(Box::new(ToolError::InvalidArguments(vec![ArgumentViolation {
+                    path: "/amount".to_string(),
+                    message: format!("{} is out of range", x),
+                }])));
+            }
+            Some(x) if x.fract() == 0.0 => json!(x as i64),
+            Some(x) => json!(x),
+            None => json!(1),
+        };
+        let value = state.variables.increment(&args.name, &amount)?;
+        Ok(format!("{} = {}", args.name, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scripting::{dice, ScriptedTool};
+
+    fn violations(arguments: Value) -> Vec<ArgumentViolation> {
+        validate_arguments(&parameters_for::<CreateLocationArgs>(), &arguments).unwrap_err()
+    }
+
+    #[test]
+    fn accepts_arguments_matching_the_schema() {
+        let arguments = json!({"name": "Cellar", "description": "Damp and dark", "travel_minutes": 5});
+        assert!(validate_arguments(&parameters_for::<CreateLocationArgs>(), &arguments).is_ok());
+    }
+
+    #[test]
+    fn rejects_undeclared_arguments() {
+        let found = violations(json!({"name": "Cellar", "description": "Damp", "size": "huge"}));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "");
+        assert!(found[0].message.contains("size"), "{}", found[0].message);
+    }
+
+    #[test]
+    fn rejects_missing_required_arguments() {
+        let found = violations(json!({"name": "Cellar"}));
+        assert_eq!(found.len(), 1);
+        assert!(found[0].message.contains("description"), "{}", found[0].message);
+    }
+
+    #[test]
+    fn rejects_arguments_of_the_wrong_type() {
+        let found = violations(json!({"name": "Cellar", "description": "Damp", "travel_minutes": "five"}));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "/travel_minutes");
+    }
+
+    #[test]
+    fn rejects_travel_times_over_a_day() {
+        let found = violations(json!({"name": "Cellar", "description": "Damp", "travel_minutes": 5000}));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "/travel_minutes");
+    }
+
+    #[test]
+    fn scenario_tools_cannot_replace_built_ins() {
+        let script = |name: &str| {
+            let source = format!(r#"fn tool() {{ #{{ name: "{}", description: "" }} }}"#, name);
+            let source = source + r#" fn run(args) { "Poof" }"#;
+            ScriptedTool::from_source(&source, dice(None)).unwrap()
+        };
+        let mut registry = ToolRegistry::builtin();
+        assert!(!registry.register_new(script("move_to")));
+        assert!(registry.register_new(script("pick_lock")));
+        assert!(!registry.register_new(script("pick_lock")));
+
+        let mut state = GameState::default();
+        let error = registry.execute("move_to", &json!({"location": "Moon"}), &mut state).unwrap_err();
+        assert!(error.to_string().contains("Unknown location"), "{}", error);
+        assert_eq!(registry.execute("pick_lock", &json!({}), &mut state).unwrap(), "Poof");
+    }
+
+    #[test]
+    fn invalid_calls_leave_the_state_alone() {
+        let mut state = GameState::default();
+        let before = state.clock;
+        let error = ToolRegistry::builtin()
+            .execute("advance_time", &json!({"days": 1e16}), &mut state)
+            .unwrap_err();
+        assert!(matches!(&error, ToolError::InvalidArguments(v) if v[0].path == "/days"), "{}", error);
+        assert_eq!(state.clock, before);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::state::TurnData;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Tokens and timings of the storyteller's model requests in one turn, from the
+/// counters Ollama sends with the last chunk of each reply. Durations are in milliseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[serde(default)]
+#[ts(export)]
+pub struct Usage {
+    /// Model replies received
+    pub requests: u32,
+    /// Prompt tokens Ollama evaluated
+    pub prompt_tokens: u32,
+    /// Tokens generated
+    pub completion_tokens: u32,
+    pub total_ms: u32,
+    /// Time spent loading the model
+    pub load_ms: u32,
+    pub prompt_eval_ms: u32,
+    /// Time spent generating
+    pub eval_ms: u32,
+    /// From the player's action to the first streamed chunk
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub first_token_ms: Option<u32>,
+    /// Largest prompt plus reply of any one request
+    pub context_tokens: u32,
+    /// Context window requested from Ollama
+    pub context_window: u32,
+}
+
+impl Usage {
+    /// Usage of a single reply; Ollama reports durations in nanoseconds. The counters
+    /// saturate rather than overflow, as they are summed over whole sessions.
+    pub fn of_reply(prompt_tokens: u32, completion_tokens: u32, durations_ns: [u64; 4]) -> Self {
+        let [total, load, prompt_eval, eval] = durations_ns.map(|ns| (ns / 1_000_000).min(u32::MAX as u64) as u32);
+        Self {
+            requests: 1,
+            prompt_tokens,
+            completion_tokens,
+            total_ms: total,
+            load_ms: load,
+            prompt_eval_ms: prompt_eval,
+            eval_ms: eval,
+            first_token_ms: None,
+            context_tokens: prompt_tokens.saturating_add(completion_tokens),
+            context_window: 0,
+        }
+    }
+
+    /// Fold another request's usage into this one
+    pub fn add(&mut self, other: &Usage) {
+        self.requests = self.requests.saturating_add(other.requests);
+        self.prompt_tokens = self.prompt_tokens.saturating_add(other.prompt_tokens);
+        self.completion_tokens = self.completion_tokens.saturating_add(other.completion_tokens);
+        self.total_ms = self.total_ms.saturating_add(other.total_ms);
+        self.load_ms = self.load_ms.saturating_add(other.load_ms);
+        self.prompt_eval_ms = self.prompt_eval_ms.saturating_add(other.prompt_eval_ms);
+        self.eval_ms = self.eval_ms.saturating_add(other.eval_ms);
+        self.first_token_ms = match (self.first_token_ms, other.first_token_ms) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.context_tokens = self.context_tokens.max(other.context_tokens);
+        self.context_window = self.context_window.max(other.context_window);
+    }
+
+    /// Generation speed, when Ollama reported how long generating took
+    pub fn tokens_per_second(&self) -> Option<f64> {
+        (self.eval_ms > 0).then(|| self.completion_tokens as f64 * 1000.0 / self.eval_ms as f64)
+    }
+}
+
+/// Usage over a whole session, for the stats panel
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[ts(export)]
+pub struct SessionStats {
+    /// Turns with recorded usage; the opening and turns from older saves have none
+    pub turns: u32,
+    pub requests: u32,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    /// Tokens generated per second of generation
+    pub tokens_per_second: Option<f64>,
+    pub mean_first_token_ms: Option<f64>,
+    /// Context used by the latest turn and the share of the window it filled, 0 to 1
+    pub context_tokens: u32,
+    pub context_window: u32,
+    pub context_usage: Option<f64>,
+    /// Largest share of the window any turn filled
+    pub peak_context_usage: Option<f64>,
+}
+
+impl SessionStats {
+    pub fn of(history: &[TurnData]) -> Self {
+        let usages = history.iter().filter_map(|t| t.usage.as_ref()).collect::<Vec<_>>();
+        let mut total = Usage::default();
+        for usage in &usages {
+            total.add(usage);
+        }
+        let first_tokens = usages.iter().filter_map(|u| u.first_token_ms).collect::<Vec<_>>();
+        let share = |u: &Usage| (u.context_window > 0).then(|| u.context_tokens as f64 / u.context_window as f64);
+        let latest = usages.last();
+        Self {
+            turns: usages.len() as u32,
+            requests: total.requests,
+            prompt_tokens: total.prompt_tokens,
+            completion_tokens: total.completion_tokens,
+            tokens_per_second: total.tokens_per_second(),
+            mean_first_token_ms: (!first_tokens.is_empty())
+                .then(|| first_tokens.iter().map(|&ms| ms as f64).sum::<f64>() / first_tokens.len() as f64),
+            context_tokens: latest.map(|u| u.context_tokens).unwrap_or(0),
+            context_window: latest.map(|u| u.context_window).unwrap_or(0),
+            context_usage: latest.and_then(|u| share(u)),
+            peak_context_usage: usages.iter().filter_map(|u| share(u)).reduce(f64::max),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_saturate_instead_of_overflowing() {
+        let huge = Usage::of_reply(u32::MAX - 10, 100, [u64::MAX; 4]);
+        assert_eq!(huge.context_tokens, u32::MAX);
+        assert_eq!(huge.total_ms, u32::MAX);
+
+        let mut total = Usage::of_reply(20, 5, [2_000_000; 4]);
+        total.add(&huge);
+        total.add(&huge);
+        assert_eq!(total.requests, 3);
+        assert_eq!(total.prompt_tokens, u32::MAX);
+        assert_eq!(total.completion_tokens, 205);
+        assert_eq!(total.eval_ms, u32::MAX);
+    }
+
+    #[test]
+    fn adding_keeps_the_fastest_first_token_and_largest_context() {
+        let mut total = Usage {
+            first_token_ms: Some(900),
+            ..Usage::of_reply(100, 50, [0; 4])
+        };
+        total.add(&Usage {
+            first_token_ms: Some(300),
+            context_window: 8192,
+            ..Usage::of_reply(40, 10, [0; 4])
+        });
+        assert_eq!(total.first_token_ms, Some(300));
+        assert_eq!((total.context_tokens, total.context_window), (150, 8192));
+    }
+}
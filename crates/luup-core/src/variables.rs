@@ -0,0 +1,227 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use ts_rs::TS;
+
+/// Type of a story variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum VarType {
+    Bool,
+    Int,
+    Float,
+    String,
+}
+
+/// Value of a story variable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(untagged)]
+pub enum VarValue {
+    Bool(bool),
+    #[ts(type = "number")]
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl VarValue {
+    pub fn var_type(&self) -> VarType {
+        match self {
+            VarValue::Bool(_) => VarType::Bool,
+            VarValue::Int(_) => VarType::Int,
+            VarValue::Float(_) => VarType::Float,
+            VarValue::String(_) => VarType::String,
+        }
+    }
+
+    /// Convert a JSON tool argument into a value of the requested type.
+    /// Small models often send "true" or "35" as strings, so those are coerced.
+    pub fn from_json(value: &Value, var_type: VarType) -> Option<Self> {
+        match (var_type, value) {
+            (VarType::Bool, Value::Bool(b)) => Some(VarValue::Bool(*b)),
+            (VarType::Bool, Value::String(s)) => s.parse().ok().map(VarValue::Bool),
+            (VarType::Int, Value::Number(n)) => n.as_i64().map(VarValue::Int),
+            (VarType::Int, Value::String(s)) => s.trim().parse().ok().map(VarValue::Int),
+            (VarType::Float, Value::Number(n)) => n.as_f64().map(VarValue::Float),
+            (VarType::Float, Value::String(s)) => s.trim().parse().ok().map(VarValue::Float),
+            (VarType::String, Value::String(s)) => Some(VarValue::String(s.clone())),
+            (VarType::String, other) => Some(VarValue::String(other.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Infer a type for a variable that has no declaration
+    fn infer(value: &Value) -> Self {
+        match value {
+            Value::Bool(b) => VarValue::Bool(*b),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => VarValue::Int(i),
+                None => VarValue::Float(n.as_f64().unwrap_or_default()),
+            },
+            Value::String(s) => VarValue::String(s.clone()),
+            other => VarValue::String(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for VarValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VarValue::Bool(b) => write!(f, "{}", b),
+            VarValue::Int(i) => write!(f, "{}", i),
+            VarValue::Float(x) => write!(f, "{}", x),
+            VarValue::String(s) => write!(f, "\"{}\"", s),
+        }
+    }
+}
+
+/// Declaration of a variable by the scenario author
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct VarDecl {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub var_type: VarType,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub initial: Option<VarValue>,
+}
+
+/// Typed key/value store for arbitrary story state
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct VariableStore {
+    #[serde(default)]
+    pub schema: Vec<VarDecl>,
+    #[serde(default)]
+    pub values: BTreeMap<String, VarValue>,
+}
+
+impl VariableStore {
+    /// Create a store from declarations, seeding their initial values
+    pub fn with_schema(schema: Vec<VarDecl>) -> Self {
+        let values = schema
+            .iter()
+            .filter_map(|d| d.initial.clone().map(|v| (d.name.clone(), v)))
+            .collect();
+        Self { schema, values }
+    }
+
+    fn declared_type(&self, name: &str) -> Option<VarType> {
+        self.schema
+            .iter()
+            .find(|d| d.name == name)
+            .map(|d| d.var_type)
+            .or_else(|| self.values.get(name).map(|v| v.var_type()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&VarValue> {
+        self.values.get(name)
+    }
+
+    /// Set a variable, checking the value against its declared or existing type
+    pub fn set(&mut self, name: &str, value: &Value) -> Result<&VarValue, Box<dyn Error + Send + Sync>> {
+        let parsed = match self.declared_type(name) {
+            Some(var_type) => VarValue::from_json(value, var_type).ok_or_else(|| {
+                format!("Variable '{}' expects a {:?} value, got {}", name, var_type, value)
+            })?,
+            None => VarValue::infer(value),
+        };
+        self.values.insert(name.to_string(), parsed);
+        Ok(&self.values[name])
+    }
+
+    /// Add to a numeric variable, starting from zero if it is unset
+    pub fn increment(&mut self, name: &str, amount: &Value) -> Result<&VarValue, Box<dyn Error + Send + Sync>> {
+        let var_type = self.declared_type(name).unwrap_or(if amount.is_i64() {
+            VarType::Int
+        } else {
+            VarType::Float
+        });
+        let new_value = match (var_type, self.values.get(name)) {
+            (VarType::Int, current) => {
+                let by = amount.as_i64().ok_or("Amount must be an integer")?;
+                let current = match current {
+                    Some(VarValue::Int(i)) => *i,
+                    _ => 0,
+                };
+                let sum = current
+                    .checked_add(by)
+                    .ok_or_else(|| format!("Adding {} to '{}' ({}) would overflow", by, name, current))?;
+                VarValue::Int(sum)
+            }
+            (VarType::Float, current) => {
+                let by = amount.as_f64().ok_or("Amount must be a number")?;
+                let current = match current {
+                    Some(VarValue::Float(x)) => *x,
+                    _ => 0.0,
+                };
+                let sum = current + by;
+                if !sum.is_finite() {
+                    return Err(format!("Adding {} to '{}' ({}) is out of range", by, name, current).into());
+                }
+                VarValue::Float(sum)
+            }
+            (other, _) => {
+                return Err(format!("Variable '{}' is a {:?} and cannot be incremented", name, other).into())
+            }
+        };
+        self.values.insert(name.to_string(), new_value);
+        Ok(&self.values[name])
+    }
+
+    /// Render every variable as a bullet list for the prompt
+    pub fn render(&self) -> String {
+        if self.values.is_empty() {
+            return "- (none)".to_string();
+        }
+        self.values
+            .iter()
+            .map(|(name, value)| {
+                match self.schema.iter().find(|d| &d.name == name) {
+                    Some(decl) if !decl.description.is_empty() => {
+                        format!("- {} = {} ({})", name, value, decl.description)
+                    }
+                    _ => format!("- {} = {}", name, value),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn increments_start_from_zero_and_keep_integers_integral() {
+        let mut vars = VariableStore::default();
+        vars.increment("coins", &json!(3)).unwrap();
+        assert_eq!(vars.increment("coins", &json!(-1)).unwrap(), &VarValue::Int(2));
+        assert_eq!(vars.increment("heat", &json!(0.5)).unwrap(), &VarValue::Float(0.5));
+    }
+
+    #[test]
+    fn overflowing_increments_fail_and_leave_the_value_alone() {
+        let mut vars = VariableStore::default();
+        vars.set("coins", &json!(i64::MAX)).unwrap();
+        let error = vars.increment("coins", &json!(1)).unwrap_err();
+        assert!(error.to_string().contains("overflow"), "{}", error);
+        assert_eq!(vars.get("coins"), Some(&VarValue::Int(i64::MAX)));
+
+        vars.set("heat", &json!(f64::MAX)).unwrap();
+        assert!(vars.increment("heat", &json!(f64::MAX)).is_err());
+        assert_eq!(vars.get("heat"), Some(&VarValue::Float(f64::MAX)));
+    }
+}
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use ts_rs::TS;
+
+/// A place in the game world
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Location {
+    pub name: String,
+    pub description: String,
+    pub discovered: bool,
+}
+
+/// A path between two locations, traversable in both directions
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Connection {
+    pub from: String,
+    pub to: String,
+    pub travel_minutes: u32,
+}
+
+/// Graph of locations and the paths between them
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WorldMap {
+    pub locations: Vec<Location>,
+    pub connections: Vec<Connection>,
+}
+
+impl WorldMap {
+    /// Create a map containing only the starting location
+    pub fn new(start_name: &str, start_description: &str) -> Self {
+        Self {
+            locations: vec![Location {
+                name: start_name.to_string(),
+                description: start_description.to_string(),
+                discovered: true,
+            }],
+            connections: Vec::new(),
+        }
+    }
+
+    /// Look up a location by name (case-insensitive)
+    pub fn get(&self, name: &str) -> Option<&Location> {
+        self.locations
+            .iter()
+            .find(|l| l.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Find the path between two locations, if they are adjacent
+    pub fn connection(&self, a: &str, b: &str) -> Option<&Connection> {
+        self.connections.iter().find(|c| {
+            (c.from.eq_ignore_ascii_case(a) && c.to.eq_ignore_ascii_case(b))
+                || (c.from.eq_ignore_ascii_case(b) && c.to.eq_ignore_ascii_case(a))
+        })
+    }
+
+    /// Names of all locations directly reachable from `name`
+    pub fn neighbours(&self, name: &str) -> Vec<String> {
+        self.connections
+            .iter()
+            .filter_map(|c| {
+                if c.from.eq_ignore_ascii_case(name) {
+                    Some(c.to.clone())
+                } else if c.to.eq_ignore_ascii_case(name) {
+                    Some(c.from.clone())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Add a new location connected to an existing one
+    pub fn add_location(
+        &mut self,
+        name: &str,
+        description: &str,
+        connected_to: &str,
+        travel_minutes: u32,
+        discovered: bool,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.get(name).is_some() {
+            return Err(format!("Location '{}' already exists", name).into());
+        }
+        let anchor = self
+            .get(connected_to)
+            .ok_or_else(|| format!("Unknown location: {}", connected_to))?
+            .name
+            .clone();
+
+        self.locations.push(Location {
+            name: name.to_string(),
+            description: description.to_string(),
+            discovered,
+        });
+        self.connections.push(Connection {
+            from: anchor,
+            to: name.to_string(),
+            travel_minutes,
+        });
+        Ok(())
+    }
+
+    /// Add a path between two existing locations, e.g. a back door that closes a loop
+    pub fn connect(&mut self, a: &str, b: &str, travel_minutes: u32) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let from = self.get(a).ok_or_else(|| format!("Unknown location: {}", a))?.name.clone();
+        let to = self.get(b).ok_or_else(|| format!("Unknown location: {}", b))?.name.clone();
+        if from == to {
+            return Err(format!("Can't connect '{}' to itself", from).into());
+        }
+        if self.connection(&from, &to).is_some() {
+            return Err(format!("'{}' and '{}' are already connected", from, to).into());
+        }
+        self.connections.push(Connection { from, to, travel_minutes });
+        Ok(())
+    }
+
+    /// Validate that `to` is adjacent to `from` and mark it discovered.
+    /// Returns the canonical location name and the travel time in minutes.
+    pub fn travel(&mut self, from: &str, to: &str) -> Result<(String, u32), Box<dyn Error + Send + Sync>> {
+        let target = self
+            .get(to)
+            .ok_or_else(|| format!("Unknown location: {}", to))?
+            .name
+            .clone();
+        let travel_minutes = self
+            .connection(from, &target)
+            .ok_or_else(|| {
+                format!(
+                    "'{}' is not reachable from '{}'. Reachable: {}",
+                    target,
+                    from,
+                    self.neighbours(from).join(", ")
+                )
+            })?
+            .travel_minutes;
+
+        if let Some(location) = self
+            .locations
+            .iter_mut()
+            .find(|l| l.name == target)
+        {
+            location.discovered = true;
+        }
+        Ok((target, travel_minutes))
+    }
+
+    /// The subgraph the player knows about
+    pub fn discovered(&self) -> WorldMap {
+        let locations: Vec<Location> = self
+            .locations
+            .iter()
+            .filter(|l| l.discovered)
+            .cloned()
+            .collect();
+        let known = |name: &str| locations.iter().any(|l| l.name == name);
+        let connections = self
+            .connections
+            .iter()
+            .filter(|c| known(&c.from) && known(&c.to))
+            .cloned()
+            .collect();
+        WorldMap { locations, connections }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Square with a tavern, and a cellar below the tavern
+    fn town() -> WorldMap {
+        let mut map = WorldMap::new("Square", "The town square");
+        map.add_location("Tavern", "Warm and loud", "Square", 5, true).unwrap();
+        map.add_location("Cellar", "Barrels in the dark", "Tavern", 2, false).unwrap();
+        map
+    }
+
+    #[test]
+    fn connecting_two_places_allows_travel_both_ways() {
+        let mut map = town();
+        assert!(map.travel("Cellar", "Square").is_err());
+
+        map.connect("cellar", "SQUARE", 7).unwrap();
+        assert_eq!(map.travel("Cellar", "Square").unwrap(), ("Square".to_string(), 7));
+        assert_eq!(map.travel("Square", "Cellar").unwrap(), ("Cellar".to_string(), 7));
+        assert!(map.get("Cellar").unwrap().discovered);
+    }
+
+    #[test]
+    fn rejects_duplicate_and_unknown_connections() {
+        let mut map = town();
+        assert!(map.connect("Tavern", "Square", 3).is_err());
+        assert!(map.connect("Square", "Tavern", 3).is_err());
+        assert!(map.connect("Square", "Harbour", 3).is_err());
+        assert!(map.connect("Square", "square", 3).is_err());
+        assert_eq!(map.connections.len(), 2);
+    }
+}
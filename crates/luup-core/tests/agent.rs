@@ -0,0 +1,243 @@
+//! `Agent::process_action` against a scripted Ollama: the messages it streams
+//! and the game state it leaves behind
+
+mod common;
+
+use common::{done, done_with_usage, text, thinking, tool_call, MockOllama, Reply};
+use luup_core::agent::{Agent, AgentMessage};
+use luup_core::prompts::PromptTemplates;
+use luup_core::scenario::Scenario;
+use luup_core::state::{GameState, TurnData};
+use luup_core::usage::SessionStats;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+const STORY: &str = "The airlock hisses open.\n\n1. Step inside\n2. Call out\n3. Wait\n";
+
+/// A fresh science fiction game whose agent talks to `mock`
+fn start_game(mock: &MockOllama) -> (Agent, GameState) {
+    let dir = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../src-tauri/scenarios/scifi"));
+    let scenario = Scenario::load(&dir).unwrap();
+    let prompts = PromptTemplates::load(&[scenario.prompts_dir()]).unwrap();
+    let mut agent = Agent::with_ollama_url(mock.url());
+    let state = agent
+        .start_new_game(&scenario, prompts, scenario.manifest.character.default_sheet())
+        .unwrap();
+    (agent, state)
+}
+
+/// Play one action, collecting everything the agent streams
+async fn play(
+    agent: &mut Agent,
+    state: &mut GameState,
+    action: &str,
+) -> (Result<(), String>, Vec<AgentMessage>) {
+    let mut messages = Vec::new();
+    let result = agent
+        .process_action(action.to_string(), state, &[], |m| messages.push(m))
+        .await
+        .map_err(|e| e.to_string());
+    (result, messages)
+}
+
+fn kinds(messages: &[AgentMessage]) -> Vec<String> {
+    messages
+        .iter()
+        .map(|m| serde_json::to_value(m).unwrap()["type"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn turn(messages: &[AgentMessage]) -> &TurnData {
+    match messages.last() {
+        Some(AgentMessage::TurnComplete(turn)) => turn,
+        other => panic!("expected the turn to complete, got {:?}", other),
+    }
+}
+
+fn error_message(messages: &[AgentMessage]) -> String {
+    messages
+        .iter()
+        .find_map(|m| match m {
+            AgentMessage::Error { message, .. } => Some(message.clone()),
+            _ => None,
+        })
+        .expect("expected an error message")
+}
+
+#[tokio::test]
+async fn streams_reasoning_and_text_into_a_turn() {
+    let mock = MockOllama::start(vec![Reply::Stream(vec![
+        thinking("They want to board."),
+        text("The airlock hisses open.\n\n"),
+        text("1. Step inside\n"),
+        text("2. Call out\n"),
+        text("3. Wait\n"),
+        done(),
+    ])])
+    .await;
+    let (mut agent, mut state) = start_game(&mock);
+
+    let (result, messages) = play(&mut agent, &mut state, "Cycle the airlock").await;
+
+    result.unwrap();
+    assert_eq!(
+        kinds(&messages),
+        ["reasoning_chunk", "text_chunk", "text_chunk", "text_chunk", "text_chunk", "usage", "turn_complete"]
+    );
+    let turn = turn(&messages);
+    assert_eq!(turn.story_text, STORY);
+    assert_eq!(turn.choices, ["Step inside", "Call out", "Wait"]);
+    assert_eq!(turn.turn_number, 0);
+}
+
+#[tokio::test]
+async fn handles_several_lines_per_network_chunk() {
+    // The whole reply in one network chunk, without a final newline
+    let lines = [text("The airlock "), text("hisses "), text("open."), done()].concat();
+    let mock = MockOllama::start(vec![Reply::Stream(vec![lines.trim_end().to_string()])]).await;
+    let (mut agent, mut state) = start_game(&mock);
+
+    let (result, messages) = play(&mut agent, &mut state, "Cycle the airlock").await;
+
+    result.unwrap();
+    assert_eq!(kinds(&messages), ["text_chunk", "text_chunk", "text_chunk", "usage", "turn_complete"]);
+    assert_eq!(turn(&messages).story_text, "The airlock hisses open.");
+}
+
+#[tokio::test]
+async fn executes_tool_calls_and_feeds_results_back() {
+    let mock = MockOllama::start(vec![
+        Reply::Stream(vec![tool_call("advance_time", json!({ "minutes": 30 })), done()]),
+        Reply::Stream(vec![text(STORY), done()]),
+    ])
+    .await;
+    let (mut agent, mut state) = start_game(&mock);
+    let mut expected_clock = state.clock;
+    expected_clock.advance(30);
+
+    let (result, messages) = play(&mut agent, &mut state, "Wait for the pressure to settle").await;
+
+    result.unwrap();
+    assert_eq!(kinds(&messages), ["tool_call", "tool_result", "text_chunk", "usage", "turn_complete"]);
+    assert_eq!(state.clock, expected_clock);
+    assert_eq!(turn(&messages).game_state.clock, expected_clock);
+
+    // The second request carries the call and its result
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    let followup = requests[1]["messages"].as_array().unwrap();
+    let call = &followup[followup.len() - 2];
+    assert_eq!(call["tool_calls"][0]["function"]["name"], "advance_time");
+    let result = &followup[followup.len() - 1];
+    assert_eq!(result["role"], "tool");
+    assert_eq!(result["tool_name"], "advance_time");
+}
+
+#[tokio::test]
+async fn adds_up_the_usage_of_every_request_in_a_turn() {
+    let mock = MockOllama::start(vec![
+        Reply::Stream(vec![tool_call("advance_time", json!({ "minutes": 30 })), done_with_usage(900, 20, 500)]),
+        Reply::Stream(vec![text(STORY), done_with_usage(1000, 80, 1500)]),
+    ])
+    .await;
+    let (mut agent, mut state) = start_game(&mock);
+
+    let (result, messages) = play(&mut agent, &mut state, "Wait").await;
+
+    result.unwrap();
+    let usage = turn(&messages).usage.clone().unwrap();
+    assert!(matches!(&messages[messages.len() - 2], AgentMessage::Usage(streamed) if *streamed == usage));
+    assert_eq!((usage.requests, usage.prompt_tokens, usage.completion_tokens), (2, 1900, 100));
+    assert_eq!((usage.eval_ms, usage.total_ms), (2000, 2200));
+    assert_eq!(usage.context_tokens, 1080);
+    assert!(usage.first_token_ms.is_some());
+
+    let stats = SessionStats::of(std::slice::from_ref(turn(&messages)));
+    assert_eq!(stats.tokens_per_second, Some(50.0));
+    assert_eq!(stats.context_usage, Some(1080.0 / usage.context_window as f64));
+}
+
+#[tokio::test]
+async fn reports_invalid_tool_arguments_to_the_model() {
+    let mock = MockOllama::start(vec![
+        Reply::Stream(vec![tool_call("advance_time", json!({ "minutes": "soon" })), done()]),
+        Reply::Stream(vec![text(STORY), done()]),
+    ])
+    .await;
+    let (mut agent, mut state) = start_game(&mock);
+    let clock = state.clock;
+
+    let (result, messages) = play(&mut agent, &mut state, "Wait").await;
+
+    result.unwrap();
+    assert_eq!(kinds(&messages), ["tool_call", "error", "text_chunk", "usage", "turn_complete"]);
+    assert!(matches!(&messages[1], AgentMessage::Error { details: Some(_), .. }));
+    assert_eq!(state.clock, clock);
+    let followup = mock.requests()[1]["messages"].as_array().unwrap().clone();
+    assert_eq!(followup.last().unwrap()["role"], "tool");
+}
+
+#[tokio::test]
+async fn handles_lines_split_across_network_chunks() {
+    let lines = [text("The airlock "), text("hisses open."), done()].concat();
+    let pieces = lines.as_bytes().chunks(7).map(|piece| String::from_utf8_lossy(piece).to_string()).collect();
+    let mock = MockOllama::start(vec![Reply::Stream(pieces)]).await;
+    let (mut agent, mut state) = start_game(&mock);
+
+    let (result, messages) = play(&mut agent, &mut state, "Cycle the airlock").await;
+
+    result.unwrap();
+    assert_eq!(kinds(&messages), ["text_chunk", "text_chunk", "usage", "turn_complete"]);
+    assert_eq!(turn(&messages).story_text, "The airlock hisses open.");
+}
+
+#[tokio::test]
+async fn malformed_line_ends_the_reply_with_an_error() {
+    let mock = MockOllama::start(vec![Reply::Stream(vec![
+        text("The airlock "),
+        "{not json\n".to_string(),
+        text("never shown"),
+        done(),
+    ])])
+    .await;
+    let (mut agent, mut state) = start_game(&mock);
+
+    let (result, messages) = play(&mut agent, &mut state, "Cycle the airlock").await;
+
+    result.unwrap();
+    assert_eq!(kinds(&messages), ["text_chunk", "error", "usage", "turn_complete"]);
+    assert!(error_message(&messages).contains("Failed to parse JSON"));
+    assert_eq!(turn(&messages).story_text, "The airlock ");
+}
+
+#[tokio::test]
+async fn disconnect_mid_stream_keeps_the_partial_story() {
+    let mock = MockOllama::start(vec![Reply::Disconnect(vec![
+        thinking("Hmm."),
+        text("The airlock hisses"),
+    ])])
+    .await;
+    let (mut agent, mut state) = start_game(&mock);
+
+    let (result, messages) = play(&mut agent, &mut state, "Cycle the airlock").await;
+
+    result.unwrap();
+    assert_eq!(kinds(&messages), ["reasoning_chunk", "text_chunk", "error", "usage", "turn_complete"]);
+    assert!(error_message(&messages).starts_with("Stream error"));
+    assert_eq!(turn(&messages).story_text, "The airlock hisses");
+}
+
+#[tokio::test]
+async fn error_status_fails_the_turn() {
+    let mock = MockOllama::start(vec![Reply::Status(404, "model \"qwen3:8b\" not found")]).await;
+    let (mut agent, mut state) = start_game(&mock);
+    let before: Value = serde_json::to_value(&state).unwrap();
+
+    let (result, messages) = play(&mut agent, &mut state, "Cycle the airlock").await;
+
+    assert!(result.unwrap_err().contains("404"));
+    // The UI is told why, so it can suggest pulling the model
+    assert_eq!(kinds(&messages), ["error"]);
+    assert_eq!(serde_json::to_value(&messages[0]).unwrap()["kind"], "model_missing");
+    assert_eq!(serde_json::to_value(&state).unwrap(), before);
+}
@@ -0,0 +1,143 @@
+//! Recording a turn to a cassette and replaying it offline
+
+mod common;
+
+use common::{done, text, thinking, tool_call, MockOllama, Reply};
+use luup_core::agent::{Agent, AgentMessage};
+use luup_core::cassette::Cassette;
+use luup_core::ollama::{ErrorKind, RequestPolicy};
+use luup_core::prompts::PromptTemplates;
+use luup_core::scenario::Scenario;
+use luup_core::state::GameState;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+/// A fresh science fiction game talking to `url` through `cassette`
+fn start_game(url: String, cassette: Cassette) -> (Agent, GameState) {
+    let dir = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../src-tauri/scenarios/scifi"));
+    let scenario = Scenario::load(&dir).unwrap();
+    let prompts = PromptTemplates::load(&[scenario.prompts_dir()]).unwrap();
+    let mut agent = Agent::with_ollama_url(url);
+    agent.set_cassette(Some(cassette));
+    let state = agent
+        .start_new_game(&scenario, prompts, scenario.manifest.character.default_sheet())
+        .unwrap();
+    (agent, state)
+}
+
+/// Play `actions` in order, returning everything streamed as JSON and the final state.
+/// The time to the first token is measured locally, so it is left out.
+async fn play(agent: &mut Agent, state: &mut GameState, actions: &[&str]) -> (Vec<Value>, Value) {
+    let mut messages = Vec::new();
+    for action in actions {
+        let result = agent
+            .process_action(action.to_string(), state, &[], |m: AgentMessage| {
+                let mut message = serde_json::to_value(m).unwrap();
+                if let Some(usage) = message.get_mut("usage") {
+                    usage.as_object_mut().unwrap().remove("first_token_ms");
+                }
+                message.as_object_mut().unwrap().remove("first_token_ms");
+                messages.push(message)
+            })
+            .await;
+        result.unwrap();
+    }
+    (messages, serde_json::to_value(&*state).unwrap())
+}
+
+fn cassette_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("luup-{}-{}.json", name, std::process::id()))
+}
+
+#[tokio::test]
+async fn replays_a_recorded_playthrough_offline() {
+    // A reply split mid-line and mid-character, a tool round and a dropped connection
+    let story = [text("The airlock hisses open. Frost crackles: ❄"), done()].concat();
+    let (head, tail) = story.as_bytes().split_at(story.find('❄').unwrap() + 1);
+    let mock = MockOllama::start(vec![
+        Reply::Stream(vec![thinking("Board the ship."), tool_call("advance_time", json!({ "minutes": 5 })), done()]),
+        Reply::Bytes(vec![head.to_vec(), tail.to_vec()]),
+        Reply::Disconnect(vec![text("The corridor lights flicker")]),
+    ])
+    .await;
+    let path = cassette_path("replay");
+    let actions = ["Cycle the airlock", "Walk down the corridor"];
+
+    let (mut agent, mut state) = start_game(mock.url(), Cassette::record(&path));
+    let (recorded, recorded_state) = play(&mut agent, &mut state, &actions).await;
+    drop(agent);
+
+    // Nothing listens on this port; everything must come from the cassette
+    let (mut agent, mut state) = start_game("127.0.0.1:9".to_string(), Cassette::replay(&path).unwrap());
+    let (replayed, replayed_state) = play(&mut agent, &mut state, &actions).await;
+
+    assert_eq!(replayed, recorded);
+    assert_eq!(replayed_state, recorded_state);
+    assert!(recorded.iter().any(|m| m["type"] == "error"));
+
+    let file: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let interactions = file["interactions"].as_array().unwrap();
+    let chats = interactions.iter().filter(|i| i["endpoint"] == "/api/chat").collect::<Vec<_>>();
+    assert_eq!(chats.len(), 3);
+    assert_eq!(chats[0]["request"], mock.requests()[0]);
+    // Pieces that aren't valid UTF-8 on their own are kept byte for byte
+    let chunks = chats[1]["chunks"].as_array().unwrap();
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks[0]["base64"].is_string());
+    assert!(recorded.iter().any(|m| m["content"].as_str().is_some_and(|c| c.ends_with('❄'))));
+    assert!(chats[2]["error"].is_string());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn replay_fails_once_the_cassette_runs_out() {
+    let mock = MockOllama::start(vec![Reply::Stream(vec![text("The airlock hisses open."), done()])]).await;
+    let path = cassette_path("exhausted");
+
+    let (mut agent, mut state) = start_game(mock.url(), Cassette::record(&path));
+    play(&mut agent, &mut state, &["Cycle the airlock"]).await;
+    drop(agent);
+
+    let (mut agent, mut state) = start_game("127.0.0.1:9".to_string(), Cassette::replay(&path).unwrap());
+    play(&mut agent, &mut state, &["Cycle the airlock"]).await;
+    let result = agent
+        .process_action("Cycle it again".to_string(), &mut state, &[], |_| {})
+        .await;
+
+    assert!(result.unwrap_err().to_string().contains("no reply"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn replays_requests_that_never_reached_ollama() {
+    let path = cassette_path("unreachable");
+    let policy = RequestPolicy {
+        max_retries: 1,
+        initial_backoff_ms: 10,
+        ..RequestPolicy::default()
+    };
+
+    let mut kinds = Vec::new();
+    for replaying in [false, true] {
+        let cassette = if replaying {
+            Cassette::replay(&path).unwrap()
+        } else {
+            Cassette::record(&path)
+        };
+        // Nothing listens on this port, whether recording or not
+        let (mut agent, mut state) = start_game("127.0.0.1:9".to_string(), cassette);
+        agent.set_request_policy(policy.clone());
+        let result = agent
+            .process_action("Cycle the airlock".to_string(), &mut state, &[], |_| {})
+            .await;
+        kinds.push(ErrorKind::of(&*result.unwrap_err()));
+    }
+
+    assert_eq!(kinds, [Some(ErrorKind::Unreachable); 2]);
+    let file: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let interactions = file["interactions"].as_array().unwrap();
+    // The request and its retry
+    assert_eq!(interactions.len(), 2);
+    assert!(interactions.iter().all(|i| i["failed"] == "unreachable" && i["error"].is_string()));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,199 @@
+//! In-process stand-in for Ollama's `/api/chat`, replaying scripted NDJSON replies
+// Each test binary uses its own part of this module
+#![allow(dead_code)]
+
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// How the mock answers one `/api/chat` request
+pub enum Reply {
+    /// A streamed reply; each piece is sent as its own network chunk
+    Stream(Vec<String>),
+    /// A streamed reply in raw pieces, which may split a character
+    Bytes(Vec<Vec<u8>>),
+    /// Send these pieces, then drop the connection before the body ends
+    Disconnect(Vec<String>),
+    /// Send these pieces, then keep the connection open without sending more
+    Stall(Vec<String>),
+    /// An error status with a plain text body
+    Status(u16, &'static str),
+}
+
+/// A mock Ollama server answering chat requests with scripted replies, in order.
+/// Other endpoints, such as `/api/embed`, get a 404.
+pub struct MockOllama {
+    addr: String,
+    requests: Arc<Mutex<Vec<Value>>>,
+}
+
+impl MockOllama {
+    pub async fn start(replies: Vec<Reply>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let replies = Arc::new(Mutex::new(VecDeque::from(replies)));
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let recorded = recorded.clone();
+                let replies = replies.clone();
+                tokio::spawn(async move { serve(socket, recorded, replies).await });
+            }
+        });
+        Self { addr, requests }
+    }
+
+    /// Host and port, as the agent expects its Ollama URL
+    pub fn url(&self) -> String {
+        self.addr.clone()
+    }
+
+    /// Bodies of the chat requests received so far
+    pub fn requests(&self) -> Vec<Value> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn serve(mut socket: TcpStream, requests: Arc<Mutex<Vec<Value>>>, replies: Arc<Mutex<VecDeque<Reply>>>) {
+    let Some((path, body)) = read_request(&mut socket).await else { return };
+    if path != "/api/chat" {
+        let _ = socket.write_all(&status_response(404, "not found")).await;
+        return;
+    }
+    requests.lock().unwrap().push(serde_json::from_slice(&body).unwrap_or(Value::Null));
+    let reply = replies.lock().unwrap().pop_front();
+
+    let (pieces, complete, stall) = match reply {
+        Some(Reply::Stream(pieces)) => (pieces.into_iter().map(String::into_bytes).collect(), true, false),
+        Some(Reply::Bytes(pieces)) => (pieces, true, false),
+        Some(Reply::Disconnect(pieces)) => (pieces.into_iter().map(String::into_bytes).collect(), false, false),
+        Some(Reply::Stall(pieces)) => (pieces.into_iter().map(String::into_bytes).collect(), false, true),
+        Some(Reply::Status(status, body)) => {
+            let _ = socket.write_all(&status_response(status, body)).await;
+            return;
+        }
+        None => {
+            let _ = socket.write_all(&status_response(500, "no more scripted replies")).await;
+            return;
+        }
+    };
+
+    let head = "HTTP/1.1 200 OK\r\ncontent-type: application/x-ndjson\r\ntransfer-encoding: chunked\r\nconnection: close\r\n\r\n";
+    if socket.write_all(head.as_bytes()).await.is_err() {
+        return;
+    }
+    for piece in pieces {
+        let chunk = [format!("{:x}\r\n", piece.len()).as_bytes(), &piece, b"\r\n"].concat();
+        if socket.write_all(&chunk).await.is_err() {
+            return;
+        }
+        // Give the client a chance to read each piece separately
+        let _ = socket.flush().await;
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+    if stall {
+        std::future::pending::<()>().await;
+    }
+    if complete {
+        let _ = socket.write_all(b"0\r\n\r\n").await;
+    }
+    let _ = socket.shutdown().await;
+}
+
+/// Read one request's path and body
+async fn read_request(socket: &mut TcpStream) -> Option<(String, Vec<u8>)> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let header_end = loop {
+        let n = socket.read(&mut buf).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        data.extend_from_slice(&buf[..n]);
+        if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&data[..header_end]).to_string();
+    let path = head.split_whitespace().nth(1)?.to_string();
+    let length = head
+        .lines()
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().ok())?
+        })
+        .unwrap_or(0);
+    while data.len() < header_end + length {
+        let n = socket.read(&mut buf).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+    Some((path, data[header_end..].to_vec()))
+}
+
+fn status_response(status: u16, body: &str) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 {} Error\r\ncontent-type: text/plain\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+    .into_bytes()
+}
+
+fn line(message: Value, done: bool) -> String {
+    let mut chunk = json!({
+        "model": "qwen3:8b",
+        "created_at": "2025-01-01T00:00:00Z",
+        "message": message,
+        "done": done,
+    });
+    if done {
+        chunk["done_reason"] = json!("stop");
+    }
+    format!("{}\n", chunk)
+}
+
+/// An NDJSON line carrying story text
+pub fn text(content: &str) -> String {
+    line(json!({ "role": "assistant", "content": content }), false)
+}
+
+/// An NDJSON line carrying a thinking model's reasoning
+pub fn thinking(content: &str) -> String {
+    line(json!({ "role": "assistant", "content": "", "thinking": content }), false)
+}
+
+/// An NDJSON line carrying a native tool call
+pub fn tool_call(name: &str, arguments: Value) -> String {
+    line(
+        json!({
+            "role": "assistant",
+            "content": "",
+            "tool_calls": [{ "function": { "name": name, "arguments": arguments } }],
+        }),
+        false,
+    )
+}
+
+/// The final NDJSON line of a reply
+pub fn done() -> String {
+    line(json!({ "role": "assistant", "content": "" }), true)
+}
+
+/// The final NDJSON line of a reply, with Ollama's token counters and timings
+pub fn done_with_usage(prompt_tokens: u32, completion_tokens: u32, eval_ms: u64) -> String {
+    let mut chunk: Value = serde_json::from_str(&done()).unwrap();
+    chunk["prompt_eval_count"] = json!(prompt_tokens);
+    chunk["eval_count"] = json!(completion_tokens);
+    chunk["eval_duration"] = json!(eval_ms * 1_000_000);
+    chunk["total_duration"] = json!((eval_ms + 100) * 1_000_000);
+    format!("{}\n", chunk)
+}
@@ -0,0 +1,96 @@
+//! `OllamaClient`'s timeouts, retries and typed errors against a scripted Ollama
+
+mod common;
+
+use common::{done, text, MockOllama, Reply};
+use luup_core::ollama::{ChatMessage, ChunkStream, ErrorKind, OllamaClient, RequestPolicy, StreamChunk};
+use tokio_stream::StreamExt;
+
+/// A client for `url` with short timeouts and backoff, so tests stay fast
+fn client(url: String, max_retries: u32) -> OllamaClient {
+    let mut client = OllamaClient::with_url(url);
+    client.set_request_policy(RequestPolicy {
+        connect_timeout_ms: 500,
+        first_token_timeout_ms: 300,
+        idle_timeout_ms: 300,
+        max_retries,
+        initial_backoff_ms: 10,
+    });
+    client
+}
+
+async fn chat(client: &OllamaClient) -> Result<ChunkStream, ErrorKind> {
+    client
+        .chat_stream(vec![ChatMessage::new("user", "Look around".to_string())], Vec::new())
+        .await
+        .map_err(|e| ErrorKind::of(&*e).expect("expected an Ollama error"))
+}
+
+#[tokio::test]
+async fn retries_a_busy_server_until_it_answers() {
+    let mock = MockOllama::start(vec![
+        Reply::Status(503, "server busy"),
+        Reply::Stream(vec![text("The airlock hisses open."), done()]),
+    ])
+    .await;
+
+    let chunks = chat(&client(mock.url(), 3)).await.unwrap().collect::<Vec<_>>().await;
+
+    assert!(matches!(&chunks[0], Ok(StreamChunk::TextChunk(t)) if t == "The airlock hisses open."));
+    assert!(matches!(&chunks[1], Ok(StreamChunk::Done(_))));
+    assert_eq!(mock.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn retries_a_reply_that_breaks_off_before_its_first_chunk() {
+    let mock = MockOllama::start(vec![
+        Reply::Disconnect(Vec::new()),
+        Reply::Stream(vec![text("The airlock hisses open."), done()]),
+    ])
+    .await;
+
+    let chunks = chat(&client(mock.url(), 3)).await.unwrap().collect::<Vec<_>>().await;
+
+    assert!(matches!(&chunks[0], Ok(StreamChunk::TextChunk(t)) if t == "The airlock hisses open."));
+    assert_eq!(mock.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn reports_a_missing_model_without_retrying() {
+    let mock = MockOllama::start(vec![Reply::Status(
+        404,
+        r#"{"error":"model \"qwen3:8b\" not found, try pulling it first"}"#,
+    )])
+    .await;
+
+    assert_eq!(chat(&client(mock.url(), 3)).await.err(), Some(ErrorKind::ModelMissing));
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn retries_when_the_first_token_never_comes() {
+    let mock = MockOllama::start(vec![Reply::Stall(Vec::new()), Reply::Stall(Vec::new())]).await;
+
+    assert_eq!(chat(&client(mock.url(), 1)).await.err(), Some(ErrorKind::Timeout));
+    assert_eq!(mock.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn a_stalled_stream_ends_with_a_timeout() {
+    let mock = MockOllama::start(vec![Reply::Stall(vec![text("The airlock")])]).await;
+
+    let chunks = chat(&client(mock.url(), 3)).await.unwrap().collect::<Vec<_>>().await;
+
+    assert_eq!(chunks.len(), 2);
+    assert!(matches!(&chunks[0], Ok(StreamChunk::TextChunk(t)) if t == "The airlock"));
+    let error = chunks[1].as_ref().unwrap_err();
+    assert_eq!(ErrorKind::of(&**error), Some(ErrorKind::Timeout));
+    // Something was streamed already, so the request isn't repeated
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn an_unreachable_server_fails_after_the_retries() {
+    // Nothing listens on this port
+    assert_eq!(chat(&client("127.0.0.1:9".to_string(), 2)).await.err(), Some(ErrorKind::Unreachable));
+}
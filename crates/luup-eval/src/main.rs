@@ -0,0 +1,226 @@
+//! Evaluation harness: plays scenarios automatically and scores the storyteller,
+//! so prompt and model changes can be compared run against run
+
+mod player;
+mod report;
+
+use clap::Parser;
+use luup_core::agent::{AgentMessage, DEFAULT_CHOICES};
+use luup_core::context::estimate_tokens;
+use luup_core::engine::DEFAULT_SCENARIO;
+use luup_core::logging::{self, LogConfig};
+use luup_core::ollama::{ModelOptions, OllamaClient};
+use luup_core::{Engine, EngineConfig, Game};
+use player::{Player, PlayerKind};
+use report::{RepetitionTracker, Report, RunReport, Summary, TurnMetrics};
+use std::error::Error;
+use std::path::PathBuf;
+use std::time::Instant;
+use tracing::{info, info_span, warn, Instrument};
+
+#[derive(Parser)]
+#[command(name = "luup-eval", about = "Play Luup automatically and score the storyteller")]
+struct Args {
+    /// Scenario to play
+    #[arg(long, default_value = DEFAULT_SCENARIO)]
+    scenario: String,
+    /// Turns per playthrough
+    #[arg(long, default_value_t = 10)]
+    turns: u32,
+    /// Number of playthroughs
+    #[arg(long, default_value_t = 1)]
+    runs: usize,
+    /// Who picks the actions
+    #[arg(long, value_enum, default_value = "random")]
+    player: PlayerKind,
+    /// Seed of the first run's random player and script dice; each later run adds one
+    #[arg(long, default_value_t = 1)]
+    seed: u64,
+    /// Ollama host and port, e.g. 127.0.0.1:11434
+    #[arg(long)]
+    ollama: Option<String>,
+    /// Storytelling model; the app's default when omitted
+    #[arg(long)]
+    model: Option<String>,
+    /// Model of the LLM player; the storytelling model when omitted
+    #[arg(long)]
+    player_model: Option<String>,
+    /// App data directory whose prompt overrides, context budgets and pipeline to evaluate.
+    /// Nothing is saved there.
+    #[arg(long)]
+    data_dir: Option<PathBuf>,
+    /// Directory of bundled scenario packs; defaults to the source tree's
+    #[arg(long)]
+    scenarios: Option<PathBuf>,
+    /// Write the report as JSON to this file
+    #[arg(long)]
+    json: Option<PathBuf>,
+    /// Write the report as Markdown to this file; it is printed either way
+    #[arg(long)]
+    markdown: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Args::parse()).await {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: Args) -> Result<(), Box<dyn Error + Send + Sync>> {
+    // The engine only logs warnings by default, so it doesn't drown out the progress
+    logging::init(LogConfig {
+        log_dir: None,
+        file_name: "luup-eval",
+        stderr: true,
+        default_filter: "warn,luup_eval=info",
+    })?;
+    let bundled = args
+        .scenarios
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../src-tauri/scenarios")));
+    let mut scenario_roots = vec![bundled];
+    scenario_roots.extend(args.data_dir.iter().map(|dir| dir.join("scenarios")));
+    let config = EngineConfig {
+        scenario_roots,
+        data_dir: args.data_dir.clone(),
+        ollama_url: args.ollama.clone(),
+        model: args.model.clone(),
+        cassette: None,
+        dice_seed: None,
+    };
+
+    let client = match &args.ollama {
+        Some(url) => OllamaClient::with_url(url.clone()),
+        None => OllamaClient::new(),
+    };
+    let model = args.model.clone().unwrap_or_else(|| client.model().to_string());
+    let player_client = client.with_model(
+        Some(args.player_model.as_deref().unwrap_or(&model)),
+        &ModelOptions::default(),
+    );
+
+    let started_at = chrono::Local::now().format("%Y-%m-%d %H:%M").to_string();
+    let mut runs = Vec::new();
+    for run in 0..args.runs {
+        let seed = args.seed + run as u64;
+        // Every progress line of the run carries its number and seed
+        let span = info_span!("run", run = run + 1, seed);
+        info!(parent: &span, runs = args.runs, "Starting run");
+        // The seed drives the scripted tools' dice too, so a run can be replayed
+        let engine = Engine::new(EngineConfig {
+            dice_seed: Some(seed),
+            ..config.clone()
+        });
+        let game = engine.new_game(Some(&args.scenario), None).await?;
+        let player = Player::new(args.player, seed, player_client.clone());
+        let turns = play_through(game, player, args.turns).instrument(span).await?;
+        runs.push(RunReport {
+            run,
+            seed,
+            summary: Summary::of(&turns),
+            turns,
+        });
+    }
+
+    let report = Report {
+        started_at,
+        scenario: args.scenario.clone(),
+        model,
+        player: format!("{:?}", args.player).to_lowercase(),
+        turns_per_run: args.turns,
+        summary: Summary::of(runs.iter().flat_map(|r| &r.turns)),
+        runs,
+    };
+    let markdown = report.to_markdown();
+    if let Some(path) = &args.json {
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+    }
+    if let Some(path) = &args.markdown {
+        std::fs::write(path, &markdown)?;
+    }
+    println!("{}", markdown);
+    Ok(())
+}
+
+/// Play `turns` turns of `game`, measuring each
+async fn play_through(
+    mut game: Game,
+    mut player: Player,
+    turns: u32,
+) -> Result<Vec<TurnMetrics>, Box<dyn Error + Send + Sync>> {
+    let mut repetition = RepetitionTracker::default();
+    let opening = game.current_turn().ok_or("The game has no opening turn")?;
+    repetition.score(&opening.story_text);
+
+    let mut metrics = Vec::new();
+    for _ in 0..turns {
+        let previous = game.current_turn().cloned().ok_or("The game has no turns")?;
+        let action = match player.choose(&previous).await {
+            Ok(action) => action,
+            Err(e) => {
+                warn!(error = %e, "The player failed to choose, taking the first choice");
+                previous.choices.first().cloned().unwrap_or_else(|| "Look around".to_string())
+            }
+        };
+        let turn = play_turn(&mut game, action, &mut repetition).await;
+        let (number, latency_ms) = (turn.turn, turn.latency_ms);
+        match &turn.error {
+            Some(error) => warn!(turn = number, action = %turn.action, latency_ms, %error, "Turn failed"),
+            None => info!(turn = number, action = %turn.action, latency_ms, "Turn played"),
+        }
+        metrics.push(turn);
+    }
+    Ok(metrics)
+}
+
+async fn play_turn(game: &mut Game, action: String, repetition: &mut RepetitionTracker) -> TurnMetrics {
+    let mut metrics = TurnMetrics {
+        turn: game.history().len() as u32,
+        action: action.clone(),
+        ..Default::default()
+    };
+    let mut streamed = String::new();
+    let started = Instant::now();
+    let result = game
+        .take_action(action, |message| match &message {
+            AgentMessage::TextChunk { content }
+            | AgentMessage::ReasoningChunk { content }
+            | AgentMessage::RulingChunk { content }
+            | AgentMessage::Dialogue { content, .. } => {
+                metrics.first_token_ms.get_or_insert(started.elapsed().as_millis() as u64);
+                streamed.push_str(content);
+            }
+            AgentMessage::ToolCall { .. } => metrics.tool_calls += 1,
+            AgentMessage::Usage(usage) if usage.requests > 0 => {
+                metrics.tokens = usage.completion_tokens as usize;
+                metrics.tokens_per_second = usage.tokens_per_second();
+            }
+            // Rejected tool calls carry the structured result the model was given
+            AgentMessage::Error { details: Some(details), .. } => match details["result"]["error"].as_str() {
+                Some("invalid_arguments") => {
+                    metrics.invalid_tool_calls += 1;
+                    metrics.schema_violations += 1;
+                }
+                Some("unknown_tool") => metrics.invalid_tool_calls += 1,
+                _ => metrics.failed_tool_calls += 1,
+            },
+            AgentMessage::Error { details: None, .. } => metrics.stream_errors += 1,
+            _ => {}
+        })
+        .await;
+    metrics.latency_ms = started.elapsed().as_millis() as u64;
+    if metrics.tokens == 0 {
+        metrics.tokens = estimate_tokens(&streamed);
+    }
+
+    match result {
+        Ok(turn) => {
+            metrics.choices_extracted = !turn.choices.iter().map(String::as_str).eq(DEFAULT_CHOICES);
+            metrics.repetition = repetition.score(&turn.story_text);
+        }
+        Err(e) => metrics.error = Some(e.to_string()),
+    }
+    metrics
+}
@@ -0,0 +1,111 @@
+use clap::ValueEnum;
+use luup_core::ollama::{ChatMessage, OllamaClient};
+use luup_core::state::TurnData;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::error::Error;
+
+const PLAYER_PROMPT: &str = "You are playing a text adventure. Read the latest part of the story and decide what \
+your character does next. Reply with the number of one of the choices, or with a short action of your own \
+in the second person, like \"Search the desk\". Reply with nothing else.";
+
+/// Longest free-form action taken from the LLM player
+const MAX_ACTION_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PlayerKind {
+    /// Pick a choice at random
+    Random,
+    /// Take the first choice not taken before in the run, to keep exploring
+    Policy,
+    /// Let a model play, choosing or writing its own actions
+    Llm,
+}
+
+/// Decides each action from the turn before it
+pub enum Player {
+    Random(StdRng),
+    Policy { taken: Vec<String> },
+    Llm(OllamaClient),
+}
+
+impl Player {
+    /// `seed` makes random runs repeatable; `client` is only used by the LLM player
+    pub fn new(kind: PlayerKind, seed: u64, client: OllamaClient) -> Self {
+        match kind {
+            PlayerKind::Random => Self::Random(StdRng::seed_from_u64(seed)),
+            PlayerKind::Policy => Self::Policy { taken: Vec::new() },
+            PlayerKind::Llm => Self::Llm(client),
+        }
+    }
+
+    pub async fn choose(&mut self, turn: &TurnData) -> Result<String, Box<dyn Error + Send + Sync>> {
+        let choices = &turn.choices;
+        match self {
+            Self::Random(rng) if !choices.is_empty() => Ok(choices[rng.gen_range(0..choices.len())].clone()),
+            Self::Policy { taken } if !choices.is_empty() => {
+                let choice = choices
+                    .iter()
+                    .find(|c| !taken.contains(c))
+                    .unwrap_or(&choices[taken.len() % choices.len()])
+                    .clone();
+                taken.push(choice.clone());
+                Ok(choice)
+            }
+            Self::Llm(client) => {
+                let numbered = choices
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| format!("{}. {}", i + 1, c))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let reply = client
+                    .chat(vec![
+                        ChatMessage::new("system", PLAYER_PROMPT.to_string()),
+                        ChatMessage::new("user", format!("{}\n\nChoices:\n{}", turn.story_text.trim(), numbered)),
+                    ])
+                    .await?;
+                Ok(parse_action(&reply, choices))
+            }
+            _ => Ok("Look around".to_string()),
+        }
+    }
+}
+
+/// A choice picked by number, or the reply's first line as a free-form action
+fn parse_action(reply: &str, choices: &[String]) -> String {
+    let line = reply.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("Look around");
+    let number = line
+        .trim_start_matches(['(', '*', '#', ' '])
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>();
+    match number.parse::<usize>() {
+        Ok(n) if (1..=choices.len()).contains(&n) => choices[n - 1].clone(),
+        _ => line.chars().take(MAX_ACTION_CHARS).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn choices() -> Vec<String> {
+        ["Open the door", "Go left", "Wait"].map(String::from).to_vec()
+    }
+
+    #[test]
+    fn numbered_replies_pick_that_choice() {
+        assert_eq!(parse_action("2", &choices()), "Go left");
+        assert_eq!(parse_action("\n**3.** Wait, because it is safer", &choices()), "Wait");
+        assert_eq!(parse_action("(1) the door", &choices()), "Open the door");
+    }
+
+    #[test]
+    fn other_replies_are_free_form_actions() {
+        assert_eq!(parse_action("Search the desk\nfor clues", &choices()), "Search the desk");
+        assert_eq!(parse_action("7 steps back", &choices()), "7 steps back");
+        assert_eq!(parse_action("   ", &choices()), "Look around");
+        assert_eq!(parse_action(&"a".repeat(500), &choices()).len(), MAX_ACTION_CHARS);
+    }
+}
@@ -0,0 +1,256 @@
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+/// What one turn of a playthrough measured
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TurnMetrics {
+    pub turn: u32,
+    pub action: String,
+    /// From sending the action to the end of the turn
+    pub latency_ms: u64,
+    /// From sending the action to the first streamed token, of any kind
+    pub first_token_ms: Option<u64>,
+    /// Tokens generated as Ollama counted them; estimated from the streamed text when
+    /// it didn't report them
+    pub tokens: usize,
+    /// Generation speed Ollama reported
+    pub tokens_per_second: Option<f64>,
+    /// Whether the story offered numbered choices, rather than the fallback ones
+    pub choices_extracted: bool,
+    pub tool_calls: usize,
+    /// Calls to tools that don't exist or with arguments that don't match the schema
+    pub invalid_tool_calls: usize,
+    /// Calls whose arguments broke the tool's schema
+    pub schema_violations: usize,
+    /// Valid calls the game refused, e.g. moving somewhere out of reach
+    pub failed_tool_calls: usize,
+    /// Stream problems such as malformed lines or dropped connections
+    pub stream_errors: usize,
+    /// Share of the story's word trigrams already seen earlier in the run
+    pub repetition: f64,
+    /// Set when the turn failed and the game did not advance
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Scores over a set of turns; rates are between 0 and 1
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Summary {
+    pub turns: usize,
+    pub failed_turns: usize,
+    pub choice_extraction_rate: f64,
+    pub tool_calls: usize,
+    /// Share of tool calls naming a real tool with valid arguments; 1 when there were none
+    pub tool_validity: f64,
+    pub schema_violations: usize,
+    pub stream_errors: usize,
+    pub mean_latency_ms: f64,
+    pub max_latency_ms: u64,
+    pub mean_first_token_ms: Option<f64>,
+    pub tokens_per_turn: f64,
+    pub mean_tokens_per_second: Option<f64>,
+    pub mean_repetition: f64,
+}
+
+impl Summary {
+    pub fn of<'a>(turns: impl IntoIterator<Item = &'a TurnMetrics>) -> Self {
+        let turns = turns.into_iter().collect::<Vec<_>>();
+        let played = turns.iter().filter(|t| t.error.is_none()).collect::<Vec<_>>();
+        let first_tokens = played.iter().filter_map(|t| t.first_token_ms).collect::<Vec<_>>();
+        let speeds = played.iter().filter_map(|t| t.tokens_per_second).collect::<Vec<_>>();
+        let tool_calls = turns.iter().map(|t| t.tool_calls).sum::<usize>();
+        let invalid = turns.iter().map(|t| t.invalid_tool_calls).sum::<usize>();
+        Self {
+            turns: turns.len(),
+            failed_turns: turns.len() - played.len(),
+            choice_extraction_rate: mean(played.iter().map(|t| if t.choices_extracted { 1.0 } else { 0.0 })),
+            tool_calls,
+            tool_validity: if tool_calls == 0 {
+                1.0
+            } else {
+                (tool_calls - invalid) as f64 / tool_calls as f64
+            },
+            schema_violations: turns.iter().map(|t| t.schema_violations).sum(),
+            stream_errors: turns.iter().map(|t| t.stream_errors).sum(),
+            mean_latency_ms: mean(played.iter().map(|t| t.latency_ms as f64)),
+            max_latency_ms: played.iter().map(|t| t.latency_ms).max().unwrap_or(0),
+            mean_first_token_ms: (!first_tokens.is_empty()).then(|| mean(first_tokens.iter().map(|&ms| ms as f64))),
+            tokens_per_turn: mean(played.iter().map(|t| t.tokens as f64)),
+            mean_tokens_per_second: (!speeds.is_empty()).then(|| mean(speeds.iter().copied())),
+            mean_repetition: mean(played.iter().map(|t| t.repetition)),
+        }
+    }
+}
+
+/// One playthrough
+#[derive(Debug, Clone, Serialize)]
+pub struct RunReport {
+    pub run: usize,
+    pub seed: u64,
+    pub summary: Summary,
+    pub turns: Vec<TurnMetrics>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub started_at: String,
+    pub scenario: String,
+    pub model: String,
+    pub player: String,
+    pub turns_per_run: u32,
+    pub summary: Summary,
+    pub runs: Vec<RunReport>,
+}
+
+impl Report {
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Luup evaluation: {}\n", self.scenario);
+        let _ = writeln!(
+            out,
+            "{} · model `{}` · {} player · {} runs of {} turns\n",
+            self.started_at,
+            self.model,
+            self.player,
+            self.runs.len(),
+            self.turns_per_run
+        );
+        let _ = writeln!(out, "| Run | Turns | Failed | Choices | Tool validity | Schema violations | Stream errors | Latency (mean/max) | First token | Tokens/turn | Tokens/s | Repetition |");
+        let _ = writeln!(out, "|---|---|---|---|---|---|---|---|---|---|---|---|");
+        for run in &self.runs {
+            summary_row(&mut out, &format!("{} (seed {})", run.run + 1, run.seed), &run.summary);
+        }
+        summary_row(&mut out, "**All**", &self.summary);
+
+        let failures = self
+            .runs
+            .iter()
+            .flat_map(|run| run.turns.iter().map(move |turn| (run.run, turn)))
+            .filter_map(|(run, turn)| turn.error.as_ref().map(|e| (run, turn.turn, e)))
+            .collect::<Vec<_>>();
+        if !failures.is_empty() {
+            let _ = writeln!(out, "\n## Failed turns\n");
+            for (run, turn, error) in failures {
+                let _ = writeln!(out, "- Run {}, turn {}: {}", run + 1, turn, error);
+            }
+        }
+        out
+    }
+}
+
+fn summary_row(out: &mut String, label: &str, s: &Summary) {
+    let first_token = s
+        .mean_first_token_ms
+        .map(|ms| format!("{:.0} ms", ms))
+        .unwrap_or_else(|| "-".to_string());
+    let speed = s
+        .mean_tokens_per_second
+        .map(|speed| format!("{:.1}", speed))
+        .unwrap_or_else(|| "-".to_string());
+    let _ = writeln!(
+        out,
+        "| {} | {} | {} | {:.0}% | {:.0}% ({} calls) | {} | {} | {:.0}/{} ms | {} | {:.0} | {} | {:.0}% |",
+        label,
+        s.turns,
+        s.failed_turns,
+        s.choice_extraction_rate * 100.0,
+        s.tool_validity * 100.0,
+        s.tool_calls,
+        s.schema_violations,
+        s.stream_errors,
+        s.mean_latency_ms,
+        s.max_latency_ms,
+        first_token,
+        s.tokens_per_turn,
+        speed,
+        s.mean_repetition * 100.0
+    );
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+/// Tracks the word trigrams of a run's stories, to measure how much each turn repeats
+#[derive(Default)]
+pub struct RepetitionTracker {
+    seen: HashSet<[String; 3]>,
+}
+
+impl RepetitionTracker {
+    /// Share of `text`'s trigrams seen in earlier texts; then remembers them
+    pub fn score(&mut self, text: &str) -> f64 {
+        let words = text
+            .split(|c: char| !c.is_alphanumeric() && c != '\'')
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+        let trigrams = words
+            .windows(3)
+            .map(|w| [w[0].clone(), w[1].clone(), w[2].clone()])
+            .collect::<Vec<_>>();
+        if trigrams.is_empty() {
+            return 0.0;
+        }
+        let repeated = trigrams.iter().filter(|t| self.seen.contains(*t)).count();
+        self.seen.extend(trigrams.iter().cloned());
+        repeated as f64 / trigrams.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(latency_ms: u64, error: Option<&str>) -> TurnMetrics {
+        TurnMetrics {
+            latency_ms,
+            choices_extracted: error.is_none(),
+            error: error.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn failed_turns_are_left_out_of_the_means() {
+        let turns = [turn(100, None), turn(300, None), turn(90_000, Some("timed out"))];
+        let summary = Summary::of(&turns);
+
+        assert_eq!((summary.turns, summary.failed_turns), (3, 1));
+        assert_eq!(summary.mean_latency_ms, 200.0);
+        assert_eq!(summary.max_latency_ms, 300);
+        assert_eq!(summary.choice_extraction_rate, 1.0);
+    }
+
+    #[test]
+    fn tool_validity_is_perfect_without_calls() {
+        assert_eq!(Summary::of(&[turn(100, None)]).tool_validity, 1.0);
+
+        let mut called = turn(100, None);
+        called.tool_calls = 4;
+        called.invalid_tool_calls = 1;
+        assert_eq!(Summary::of(&[called]).tool_validity, 0.75);
+    }
+
+    #[test]
+    fn means_of_nothing_are_zero() {
+        assert_eq!(mean(std::iter::empty()), 0.0);
+        assert_eq!(mean([1.0, 2.0].into_iter()), 1.5);
+        assert_eq!(Summary::of(&[]).mean_first_token_ms, None);
+    }
+
+    #[test]
+    fn repetition_counts_trigrams_seen_in_earlier_texts() {
+        let mut tracker = RepetitionTracker::default();
+        assert_eq!(tracker.score("The wind howls over the moor"), 0.0);
+        // Two of the four trigrams ("the wind howls", "wind howls over") were seen before
+        assert_eq!(tracker.score("The WIND howls over, quietly now"), 0.5);
+        assert_eq!(tracker.score("Hush"), 0.0);
+    }
+}
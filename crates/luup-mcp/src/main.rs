@@ -0,0 +1,423 @@
+//! Model Context Protocol server over stdio, so other agent hosts can play Luup
+//! sessions. Game actions and the game's tools are MCP tools; each session's state
+//! and turn history are MCP resources.
+
+use clap::Parser;
+use luup_core::engine::DEFAULT_SCENARIO;
+use luup_core::logging::{self, LogConfig};
+use luup_core::ollama::Tool as GameTool;
+use luup_core::tools::{parameters_for, ToolRegistry};
+use luup_core::{Engine, EngineConfig, SessionError, SessionManager};
+use rmcp::model::{
+    AnnotateAble, CallToolRequestParams, CallToolResult, Content, Implementation, JsonObject, ListResourceTemplatesResult,
+    ListResourcesResult, ListToolsResult, PaginatedRequestParams, ProtocolVersion, RawResource, RawResourceTemplate,
+    ReadResourceRequestParams, ReadResourceResult, ResourceContents, ServerCapabilities, ServerInfo, Tool,
+};
+use rmcp::service::RequestContext;
+use rmcp::{ErrorData as McpError, RoleServer, ServerHandler, ServiceExt};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+use tracing::info;
+
+/// The desktop app's identifier; its data directory is named after it
+const APP_IDENTIFIER: &str = "com.luup.app";
+
+const MIME_JSON: &str = "application/json";
+
+#[derive(Parser)]
+#[command(name = "luup-mcp", about = "Serve Luup sessions to MCP clients over stdio")]
+struct Args {
+    /// Ollama host and port, e.g. 127.0.0.1:11434
+    #[arg(long)]
+    ollama: Option<String>,
+    /// App data directory with saves, scenarios and overrides; defaults to the desktop app's
+    #[arg(long)]
+    data_dir: Option<PathBuf>,
+    /// Directory of bundled scenario packs; defaults to the source tree's
+    #[arg(long)]
+    scenarios: Option<PathBuf>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct NoArgs {}
+
+#[derive(Deserialize, JsonSchema)]
+struct StartGameArgs {
+    /// Scenario id from list_scenarios; the default scenario when omitted
+    scenario_id: Option<String>,
+    /// Name of the player character; everything else comes from the scenario's default character
+    character_name: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct LoadGameArgs {
+    /// Save id from list_saves
+    save_id: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct SessionArgs {
+    /// Session id returned by start_game or load_game
+    session_id: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct TakeActionArgs {
+    /// Session id returned by start_game or load_game
+    session_id: String,
+    /// What the player does: one of the offered choices or anything else
+    action: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ListTurnsArgs {
+    /// Session id returned by start_game or load_game
+    session_id: String,
+    /// Only list turns from this turn number on
+    since: Option<u32>,
+}
+
+/// A turn without its game state snapshot
+#[derive(Serialize)]
+struct TurnSummary<'a> {
+    turn_number: u32,
+    story_text: &'a str,
+    choices: &'a [String],
+}
+
+/// The game actions offered besides the game's own tools
+const ACTIONS: &[(&str, &str)] = &[
+    ("list_scenarios", "List the scenario packs a game can be started in"),
+    ("list_saves", "List saved games, most recently played first"),
+    ("start_game", "Start a new game; returns its session id and the opening scene"),
+    ("load_game", "Resume a saved game; returns its session id and the latest turn"),
+    ("take_action", "Play a turn: the storyteller narrates what happens and offers new choices"),
+    ("get_state", "Read a session's current game state"),
+    ("list_turns", "List a session's turns: story text and choices"),
+];
+
+fn action_parameters(name: &str) -> Value {
+    let parameters = match name {
+        "start_game" => parameters_for::<StartGameArgs>(),
+        "load_game" => parameters_for::<LoadGameArgs>(),
+        "get_state" => parameters_for::<SessionArgs>(),
+        "take_action" => parameters_for::<TakeActionArgs>(),
+        "list_turns" => parameters_for::<ListTurnsArgs>(),
+        _ => parameters_for::<NoArgs>(),
+    };
+    serde_json::to_value(parameters).unwrap_or_default()
+}
+
+fn mcp_tool(name: &str, description: &str, schema: Value) -> Tool {
+    let schema = match schema {
+        Value::Object(schema) => schema,
+        _ => JsonObject::new(),
+    };
+    Tool::new(name.to_string(), description.to_string(), schema)
+}
+
+/// A game tool as an MCP tool, which also needs to know which session to act on
+fn game_tool(tool: &GameTool) -> Tool {
+    let mut schema = serde_json::to_value(&tool.function.parameters).unwrap_or_default();
+    if let Some(properties) = schema["properties"].as_object_mut() {
+        properties.insert(
+            "session_id".to_string(),
+            json!({ "type": "string", "description": "Session whose game state to change" }),
+        );
+    }
+    if let Some(required) = schema["required"].as_array_mut() {
+        required.insert(0, json!("session_id"));
+    }
+    let description = format!("Game tool: {}", tool.function.description);
+    mcp_tool(&tool.function.name, &description, schema)
+}
+
+fn parse<A: DeserializeOwned>(arguments: Option<JsonObject>) -> Result<A, McpError> {
+    serde_json::from_value(Value::Object(arguments.unwrap_or_default()))
+        .map_err(|e| McpError::invalid_params(e.to_string(), None))
+}
+
+fn json_result(value: impl Serialize) -> CallToolResult {
+    CallToolResult::structured(serde_json::to_value(value).unwrap_or_default())
+}
+
+/// Failed game operations are reported to the calling agent as tool errors
+fn error_result(e: impl ToString) -> CallToolResult {
+    CallToolResult::error(vec![Content::text(e.to_string())])
+}
+
+fn json_resource(uri: &str, value: impl Serialize) -> Result<ReadResourceResult, McpError> {
+    let text = serde_json::to_string_pretty(&value).map_err(|e| McpError::internal_error(e.to_string(), None))?;
+    Ok(ReadResourceResult {
+        contents: vec![ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(MIME_JSON.to_string()),
+            text,
+            meta: None,
+        }],
+    })
+}
+
+#[derive(Clone)]
+struct LuupMcp {
+    sessions: SessionManager,
+}
+
+impl LuupMcp {
+    /// Built-in game tools plus any scenario tools of the sessions in memory
+    async fn game_tools(&self) -> Vec<GameTool> {
+        let mut tools: BTreeMap<String, GameTool> = ToolRegistry::builtin()
+            .definitions()
+            .into_iter()
+            .map(|t| (t.function.name.clone(), t))
+            .collect();
+        for id in self.sessions.session_ids() {
+            if let Ok(game) = self.sessions.game(&id).await {
+                for tool in game.tool_definitions() {
+                    tools.entry(tool.function.name.clone()).or_insert(tool);
+                }
+            }
+        }
+        tools.into_values().collect()
+    }
+
+    async fn start_game(&self, args: StartGameArgs) -> Result<CallToolResult, SessionError> {
+        let scenario_id = args.scenario_id.as_deref().unwrap_or(DEFAULT_SCENARIO);
+        let character = match args.character_name {
+            Some(name) => {
+                let scenario = self.sessions.engine().find_scenario(scenario_id).await?;
+                let mut sheet = scenario.manifest.character.default_sheet();
+                sheet.name = name;
+                Some(sheet)
+            }
+            None => None,
+        };
+        let game = self.sessions.new_game(Some(scenario_id), character).await?;
+        Ok(json_result(json!({
+            "session_id": game.id(),
+            "name": game.name(),
+            "turn": game.current_turn(),
+        })))
+    }
+
+    async fn run_action(&self, name: &str, arguments: Option<JsonObject>) -> Result<CallToolResult, McpError> {
+        let result = match name {
+            "list_scenarios" => Ok(json_result(self.sessions.engine().list_scenarios().await)),
+            "list_saves" => Ok(json_result(self.sessions.engine().list_saves().await)),
+            "start_game" => self.start_game(parse(arguments)?).await,
+            "load_game" => {
+                let args: LoadGameArgs = parse(arguments)?;
+                self.sessions.load_game(&args.save_id).await.map(|game| {
+                    json_result(json!({
+                        "session_id": game.id(),
+                        "name": game.name(),
+                        "turn": game.current_turn(),
+                    }))
+                })
+            }
+            "take_action" => {
+                let args: TakeActionArgs = parse(arguments)?;
+                self.sessions.take_action(&args.session_id, args.action).await.map(json_result)
+            }
+            "get_state" => {
+                let args: SessionArgs = parse(arguments)?;
+                self.sessions.game(&args.session_id).await.map(|game| json_result(game.state()))
+            }
+            "list_turns" => {
+                let args: ListTurnsArgs = parse(arguments)?;
+                self.sessions.game(&args.session_id).await.map(|game| {
+                    let since = args.since.unwrap_or(0);
+                    let turns: Vec<TurnSummary> = game
+                        .history()
+                        .iter()
+                        .filter(|t| t.turn_number >= since)
+                        .map(|t| TurnSummary {
+                            turn_number: t.turn_number,
+                            story_text: &t.story_text,
+                            choices: &t.choices,
+                        })
+                        .collect();
+                    json_result(json!({ "turns": turns }))
+                })
+            }
+            _ => return Err(McpError::invalid_params(format!("Unknown tool: {}", name), None)),
+        };
+        Ok(result.unwrap_or_else(error_result))
+    }
+
+    async fn run_game_tool(&self, name: &str, arguments: Option<JsonObject>) -> Result<CallToolResult, McpError> {
+        let mut arguments = arguments.unwrap_or_default();
+        let session_id = match arguments.remove("session_id") {
+            Some(Value::String(id)) => id,
+            _ => return Err(McpError::invalid_params("session_id is required", None)),
+        };
+        let result = self.sessions.run_tool(&session_id, name, &Value::Object(arguments)).await;
+        Ok(match result {
+            Ok(output) => CallToolResult::success(vec![Content::text(output)]),
+            Err(e) => error_result(e),
+        })
+    }
+}
+
+impl ServerHandler for LuupMcp {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            protocol_version: ProtocolVersion::default(),
+            capabilities: ServerCapabilities::builder().enable_tools().enable_resources().build(),
+            server_info: Implementation {
+                name: "luup".to_string(),
+                title: Some("Luup".to_string()),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                description: None,
+                icons: None,
+                website_url: None,
+            },
+            instructions: Some(
+                "Luup is a text adventure told by a language model. Start or load a game to get a session id, \
+                 then play it with take_action. Game tools change a session's state directly."
+                    .to_string(),
+            ),
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let mut tools: Vec<Tool> = ACTIONS
+            .iter()
+            .map(|(name, description)| mcp_tool(name, description, action_parameters(name)))
+            .collect();
+        tools.extend(
+            self.game_tools()
+                .await
+                .iter()
+                .filter(|t| !ACTIONS.iter().any(|(name, _)| *name == t.function.name))
+                .map(game_tool),
+        );
+        Ok(ListToolsResult::with_all_items(tools))
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        info!(tool = %request.name, "MCP tool call");
+        if ACTIONS.iter().any(|(name, _)| *name == request.name) {
+            self.run_action(&request.name, request.arguments).await
+        } else {
+            self.run_game_tool(&request.name, request.arguments).await
+        }
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let mut resources = Vec::new();
+        for id in self.sessions.session_ids() {
+            for (kind, description) in [("state", "Current game state"), ("turns", "Turn history")] {
+                let mut resource = RawResource::new(format!("luup://sessions/{}/{}", id, kind), format!("{} {}", id, kind));
+                resource.description = Some(description.to_string());
+                resource.mime_type = Some(MIME_JSON.to_string());
+                resources.push(resource.no_annotation());
+            }
+        }
+        Ok(ListResourcesResult::with_all_items(resources))
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        let templates = [
+            ("luup://sessions/{session_id}/state", "Game state", "A session's current game state"),
+            ("luup://sessions/{session_id}/turns", "Turn history", "Every turn of a session, with state snapshots"),
+            ("luup://sessions/{session_id}/turns/{turn_number}", "Turn", "One turn of a session"),
+        ]
+        .into_iter()
+        .map(|(uri_template, name, description)| {
+            RawResourceTemplate {
+                uri_template: uri_template.to_string(),
+                name: name.to_string(),
+                title: None,
+                description: Some(description.to_string()),
+                mime_type: Some(MIME_JSON.to_string()),
+                icons: None,
+            }
+            .no_annotation()
+        })
+        .collect();
+        Ok(ListResourceTemplatesResult::with_all_items(templates))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let uri = request.uri.as_str();
+        let not_found = || McpError::resource_not_found(format!("No resource {}", uri), None);
+        let path: Vec<&str> = uri.strip_prefix("luup://sessions/").ok_or_else(not_found)?.split('/').collect();
+        let game = self.sessions.game(path[0]).await.map_err(|_| not_found())?;
+        match path[1..] {
+            ["state"] => json_resource(uri, game.state()),
+            ["turns"] => json_resource(uri, game.history()),
+            ["turns", turn_number] => {
+                let turn = turn_number.parse().ok().and_then(|n| game.turn(n)).ok_or_else(not_found)?;
+                json_resource(uri, turn)
+            }
+            _ => Err(not_found()),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Args::parse()).await {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: Args) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let data_dir = args
+        .data_dir
+        .or_else(|| dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER)))
+        .ok_or("Can't find the app data directory; pass --data-dir")?;
+    // stdout carries the protocol, so logs go to stderr and <data dir>/logs
+    let _log_guard = logging::init(LogConfig {
+        log_dir: Some(data_dir.join("logs")),
+        file_name: "luup-mcp",
+        stderr: true,
+        default_filter: "info",
+    })?;
+    let bundled = args
+        .scenarios
+        .unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../src-tauri/scenarios")));
+    let engine = Engine::new(EngineConfig {
+        scenario_roots: vec![bundled, data_dir.join("scenarios")],
+        data_dir: Some(data_dir),
+        ollama_url: args.ollama,
+        model: None,
+        cassette: None,
+        dice_seed: None,
+    });
+
+    let server = LuupMcp {
+        sessions: SessionManager::new(engine),
+    }
+    .serve(rmcp::transport::stdio())
+    .await?;
+    server.waiting().await?;
+    Ok(())
+}
@@ -0,0 +1,340 @@
+//! Game server: the desktop app's commands as a REST API, with each session's
+//! `AgentMessage`s streamed over a WebSocket, so the game can be played in a browser
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::Response;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use clap::Parser;
+use luup_core::character::CharacterSheet;
+use luup_core::logging::{self, LogConfig};
+use luup_core::manager::SessionEvent;
+use luup_core::ollama::{ChatMessage, ErrorKind, DEFAULT_URL};
+use luup_core::scenario::ScenarioSummary;
+use luup_core::session::SaveGame;
+use luup_core::state::TurnData;
+use luup_core::usage::SessionStats;
+use luup_core::world::WorldMap;
+use luup_core::engine::InvalidRequest;
+use luup_core::{Engine, EngineConfig, SessionError, SessionManager};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tower_http::services::{ServeDir, ServeFile};
+use tracing::{info, warn};
+
+/// The desktop app's identifier; its data directory is named after it
+const APP_IDENTIFIER: &str = "com.luup.app";
+
+#[derive(Parser)]
+#[command(name = "luup-server", about = "Serve Luup to browsers over HTTP")]
+struct Args {
+    /// Address to listen on; only this machine can connect unless `--expose` is given
+    #[arg(long, default_value = "127.0.0.1:8787")]
+    bind: String,
+    /// Allow binding an address other machines can reach, e.g. 0.0.0.0:8787
+    #[arg(long)]
+    expose: bool,
+    /// Token browsers must send to change the Ollama server; without one it can't be changed over HTTP
+    #[arg(long)]
+    admin_token: Option<String>,
+    /// Ollama host and port, e.g. 127.0.0.1:11434; can be changed from the settings page
+    #[arg(long)]
+    ollama: Option<String>,
+    /// App data directory with saves, scenarios and overrides; defaults to the desktop app's
+    #[arg(long)]
+    data_dir: Option<PathBuf>,
+    /// Directory of bundled scenario packs; defaults to the source tree's
+    #[arg(long)]
+    scenarios: Option<PathBuf>,
+    /// Built frontend to serve (`npm run build`); defaults to the source tree's `dist`
+    #[arg(long)]
+    static_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OllamaConfig {
+    ip_address: String,
+}
+
+#[derive(Deserialize)]
+struct NewGameRequest {
+    scenario_id: Option<String>,
+    character: Option<CharacterSheet>,
+}
+
+#[derive(Deserialize)]
+struct ActionRequest {
+    action: String,
+}
+
+struct AppState {
+    ollama_config: Mutex<OllamaConfig>,
+    sessions: SessionManager,
+    admin_token: Option<String>,
+}
+
+type SharedState = Arc<AppState>;
+type ApiError = (StatusCode, String);
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+fn api_error(e: SessionError) -> ApiError {
+    let status = match e {
+        SessionError::NotFound(_) => StatusCode::NOT_FOUND,
+        SessionError::Busy(_) => StatusCode::CONFLICT,
+        // Unknown scenario or an invalid character
+        SessionError::Failed(ref e) if e.is::<InvalidRequest>() => StatusCode::BAD_REQUEST,
+        // Ollama's failures are the upstream server's, not ours
+        SessionError::Failed(ref e) => match ErrorKind::of(&**e) {
+            Some(ErrorKind::Timeout) => StatusCode::GATEWAY_TIMEOUT,
+            Some(_) => StatusCode::BAD_GATEWAY,
+            None => StatusCode::INTERNAL_SERVER_ERROR,
+        },
+    };
+    (status, e.to_string())
+}
+
+async fn list_scenarios(State(state): State<SharedState>) -> ApiResult<Vec<ScenarioSummary>> {
+    Ok(Json(state.sessions.engine().list_scenarios().await))
+}
+
+/// Start a game in a scenario; without a character the scenario's default one is used
+async fn start_new_game(State(state): State<SharedState>, Json(request): Json<NewGameRequest>) -> ApiResult<String> {
+    let game = state
+        .sessions
+        .new_game(request.scenario_id.as_deref(), request.character)
+        .await
+        .map_err(api_error)?;
+    Ok(Json(game.id().to_string()))
+}
+
+/// Resume a saved game; returns its session id
+async fn load_game(State(state): State<SharedState>, Path(save_id): Path<String>) -> ApiResult<String> {
+    let game = state.sessions.load_game(&save_id).await.map_err(api_error)?;
+    Ok(Json(game.id().to_string()))
+}
+
+async fn get_turn(
+    State(state): State<SharedState>,
+    Path((session_id, turn_number)): Path<(String, u32)>,
+) -> ApiResult<TurnData> {
+    state
+        .sessions
+        .game(&session_id)
+        .await
+        .map_err(api_error)?
+        .turn(turn_number)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Turn not found".to_string()))
+}
+
+/// Play a turn, streaming its messages to the session's WebSockets. The turn runs
+/// to completion even if the request is dropped, e.g. by a phone going to sleep.
+async fn submit_action_stream(
+    State(state): State<SharedState>,
+    Path(session_id): Path<String>,
+    Json(request): Json<ActionRequest>,
+) -> ApiResult<TurnData> {
+    state
+        .sessions
+        .take_action(&session_id, request.action)
+        .await
+        .map(Json)
+        .map_err(api_error)
+}
+
+/// Render the exact messages the next action would send to the model
+async fn preview_prompt(
+    State(state): State<SharedState>,
+    Path(session_id): Path<String>,
+    Json(request): Json<ActionRequest>,
+) -> ApiResult<Vec<ChatMessage>> {
+    state
+        .sessions
+        .game(&session_id)
+        .await
+        .map_err(api_error)?
+        .preview_prompt(&request.action)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+async fn get_world_map(State(state): State<SharedState>, Path(session_id): Path<String>) -> ApiResult<WorldMap> {
+    Ok(Json(state.sessions.game(&session_id).await.map_err(api_error)?.world_map()))
+}
+
+async fn get_session_stats(
+    State(state): State<SharedState>,
+    Path(session_id): Path<String>,
+) -> ApiResult<SessionStats> {
+    Ok(Json(state.sessions.game(&session_id).await.map_err(api_error)?.stats()))
+}
+
+async fn list_saves(State(state): State<SharedState>) -> ApiResult<Vec<SaveGame>> {
+    Ok(Json(state.sessions.engine().list_saves().await))
+}
+
+async fn get_ollama_config(State(state): State<SharedState>) -> ApiResult<OllamaConfig> {
+    Ok(Json(state.ollama_config.lock().unwrap().clone()))
+}
+
+/// Check the request's `Authorization: Bearer <token>` against the server's admin token
+fn authorize(state: &AppState, headers: &HeaderMap) -> Result<(), ApiError> {
+    let Some(expected) = &state.admin_token else {
+        return Err((StatusCode::FORBIDDEN, "Start luup-server with --admin-token to allow this".to_string()));
+    };
+    let given = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    // Compare every byte so the time taken doesn't reveal how much of the token matched
+    let matches = given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0;
+    if matches {
+        Ok(())
+    } else {
+        Err((StatusCode::UNAUTHORIZED, "Wrong or missing admin token".to_string()))
+    }
+}
+
+/// Games started or loaded from now on use the new Ollama server; needs the admin token
+async fn set_ollama_config(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    Json(config): Json<OllamaConfig>,
+) -> ApiResult<()> {
+    authorize(&state, &headers)?;
+    let mut engine_config = state.sessions.engine().config().clone();
+    engine_config.ollama_url = Some(config.ip_address.clone());
+    state.sessions.set_engine(Engine::new(engine_config));
+    *state.ollama_config.lock().unwrap() = config;
+    Ok(Json(()))
+}
+
+async fn stream_events(
+    ws: WebSocketUpgrade,
+    Path(session_id): Path<String>,
+    State(state): State<SharedState>,
+) -> Response {
+    let events = state.sessions.subscribe();
+    ws.on_upgrade(move |socket| forward_events(socket, session_id, events))
+}
+
+/// Send a session's stream messages to a WebSocket as JSON until it closes
+async fn forward_events(
+    mut socket: WebSocket,
+    session_id: String,
+    mut events: broadcast::Receiver<SessionEvent>,
+) {
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) if event.session_id == session_id => {
+                    let Ok(text) = serde_json::to_string(&event.message) else { continue };
+                    if socket.send(Message::Text(text.into())).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(session = %session_id, skipped, "WebSocket stream fell behind");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            // Clients only listen, so anything but a close is ignored
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+fn router(state: SharedState, static_dir: Option<PathBuf>) -> Router {
+    let api = Router::new()
+        .route("/scenarios", get(list_scenarios))
+        .route("/sessions", post(start_new_game))
+        .route("/sessions/{session_id}/turns/{turn_number}", get(get_turn))
+        .route("/sessions/{session_id}/actions", post(submit_action_stream))
+        .route("/sessions/{session_id}/preview", post(preview_prompt))
+        .route("/sessions/{session_id}/map", get(get_world_map))
+        .route("/sessions/{session_id}/stats", get(get_session_stats))
+        .route("/sessions/{session_id}/events", get(stream_events))
+        .route("/saves", get(list_saves))
+        .route("/saves/{save_id}/load", post(load_game))
+        .route("/config", get(get_ollama_config).put(set_ollama_config))
+        .fallback(|| async { (StatusCode::NOT_FOUND, "No such endpoint") })
+        .with_state(state);
+    let app = Router::new().nest("/api", api);
+    match static_dir {
+        // Unknown paths get index.html so the frontend's routes survive a reload
+        Some(dir) => app.fallback_service(ServeDir::new(&dir).fallback(ServeFile::new(dir.join("index.html")))),
+        None => app,
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Args::parse()).await {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: Args) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let data_dir = args
+        .data_dir
+        .or_else(|| dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER)))
+        .ok_or("Can't find the app data directory; pass --data-dir")?;
+    let _log_guard = logging::init(LogConfig {
+        log_dir: Some(data_dir.join("logs")),
+        file_name: "luup-server",
+        stderr: true,
+        default_filter: "info",
+    })?;
+    let bundled = args
+        .scenarios
+        .unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../src-tauri/scenarios")));
+    let ollama = args.ollama.unwrap_or_else(|| DEFAULT_URL.to_string());
+    let engine = Engine::new(EngineConfig {
+        scenario_roots: vec![bundled, data_dir.join("scenarios")],
+        data_dir: Some(data_dir),
+        ollama_url: Some(ollama.clone()),
+        model: None,
+        cassette: None,
+        dice_seed: None,
+    });
+
+    let static_dir = args
+        .static_dir
+        .unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../dist")));
+    let static_dir = if static_dir.join("index.html").is_file() {
+        Some(static_dir)
+    } else {
+        warn!(dir = %static_dir.display(), "No frontend found; serving the API only (run `npm run build`)");
+        None
+    };
+
+    let state = Arc::new(AppState {
+        ollama_config: Mutex::new(OllamaConfig { ip_address: ollama }),
+        sessions: SessionManager::new(engine),
+        admin_token: args.admin_token.filter(|token| !token.is_empty()),
+    });
+
+    // Sessions and saves are open to anyone who can connect, so stay local unless asked
+    let exposed = tokio::net::lookup_host(&args.bind).await?.any(|addr| !addr.ip().is_loopback());
+    if exposed && !args.expose {
+        return Err(format!("{} is reachable from other machines; pass --expose to listen on it", args.bind).into());
+    }
+    let listener = tokio::net::TcpListener::bind(&args.bind).await?;
+    info!("Serving Luup on http://{}", listener.local_addr()?);
+    axum::serve(listener, router(state, static_dir)).await?;
+    Ok(())
+}
@@ -1,88 +1,89 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod ollama;
-mod agent;
-
-use agent::{Agent, AgentMessage, GameState as AgentGameState};
+use luup_core::agent::AgentMessage;
+use luup_core::character::CharacterSheet;
+use luup_core::logging::{self, LogConfig, WorkerGuard};
+use luup_core::ollama::{ChatMessage, DEFAULT_URL};
+use luup_core::scenario::ScenarioSummary;
+use luup_core::session::SaveGame;
+use luup_core::state::TurnData;
+use luup_core::usage::SessionStats;
+use luup_core::world::WorldMap;
+use luup_core::{Engine, EngineConfig, Game};
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
-use tauri::{State, Emitter};
+use tauri::{Emitter, Manager, State};
+use tracing::error;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct TurnData {
-    turn_number: u32,
-    story_text: String,
-    choices: Vec<String>,
-    game_state: GameState,
+struct OllamaConfig {
+    ip_address: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct GameState {
-    time: String,
-    location: String,
-    outfit: String,
+struct AppState {
+    ollama_config: Mutex<OllamaConfig>,
+    engine: Engine,
+    game: Mutex<Option<Game>>,
+    /// Flushes the log file when the app exits
+    _log_guard: Option<WorkerGuard>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct OllamaConfig {
-    ip_address: String,
+/// A copy of the game being played, so no lock is held across an await
+fn current_game(state: &AppState) -> Result<Game, String> {
+    state
+        .game
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or_else(|| "No game in progress".to_string())
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct SaveGame {
-    id: String,
-    name: String,
-    last_played: String,
-    turn_count: u32,
+/// Make `game` the one being played and write it to its save file
+async fn set_game(state: &AppState, game: Game) -> Result<String, String> {
+    let id = game.id().to_string();
+    let saved = state.engine.save(&game).await;
+    *state.game.lock().map_err(|e| e.to_string())? = Some(game);
+    saved.map_err(|e| e.to_string())?;
+    Ok(id)
 }
 
-struct AppState {
-    ollama_config: Mutex<OllamaConfig>,
-    game_history: Mutex<Vec<TurnData>>,
-    agent: Mutex<Agent>,
-    current_game_state: Mutex<AgentGameState>,
+#[tauri::command]
+async fn list_scenarios(state: State<'_, AppState>) -> Result<Vec<ScenarioSummary>, String> {
+    Ok(state.engine.list_scenarios().await)
 }
 
+/// Start a game in a scenario; without a character the scenario's default one is used
 #[tauri::command]
-fn start_new_game(state: State<AppState>) -> Result<String, String> {
-    let mut history = state.game_history.lock().unwrap();
-    let mut agent = state.agent.lock().unwrap();
-    let mut current_state = state.current_game_state.lock().unwrap();
-    
-    history.clear();
-    
-    // Initialize agent and get initial state
-    *current_state = agent.start_new_game();
-    
-    // Add initial turn
-    history.push(TurnData {
-        turn_number: 0,
-        story_text: "You wake up in a dimly lit room. The air smells of old parchment and something... magical. Three doors stand before you, each humming with a different energy.".to_string(),
-        choices: vec![
-            "Open the door radiating blue light".to_string(),
-            "Open the door with ancient runes carved into it".to_string(),
-            "Open the plain wooden door".to_string(),
-        ],
-        game_state: GameState {
-            time: current_state.time.clone(),
-            location: current_state.location.clone(),
-            outfit: current_state.outfit.clone(),
-        },
-    });
-    
-    Ok("session_stub_001".to_string())
+async fn start_new_game(
+    scenario_id: Option<String>,
+    character: Option<CharacterSheet>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let game = state
+        .engine
+        .new_game(scenario_id.as_deref(), character)
+        .await
+        .map_err(|e| e.to_string())?;
+    set_game(&state, game).await
+}
+
+/// Resume a saved game; returns its session id
+#[tauri::command]
+async fn load_game(save_id: String, state: State<'_, AppState>) -> Result<String, String> {
+    let game = state.engine.load_game(&save_id).await.map_err(|e| e.to_string())?;
+    let id = game.id().to_string();
+    *state.game.lock().map_err(|e| e.to_string())? = Some(game);
+    Ok(id)
 }
 
 #[tauri::command]
 fn get_turn(_session_id: String, turn_number: u32, state: State<AppState>) -> Result<TurnData, String> {
-    let history = state.game_history.lock().unwrap();
-    
-    if let Some(turn) = history.get(turn_number as usize) {
-        Ok(turn.clone())
-    } else {
-        Err("Turn not found".to_string())
-    }
+    let game = state.game.lock().map_err(|e| e.to_string())?;
+    game.as_ref()
+        .and_then(|game| game.turn(turn_number))
+        .cloned()
+        .ok_or_else(|| "Turn not found".to_string())
 }
 
 #[tauri::command]
@@ -91,28 +92,21 @@ fn submit_action(
     action: String,
     state: State<AppState>,
 ) -> Result<TurnData, String> {
-    let mut history = state.game_history.lock().unwrap();
-    let current_turn = history.len() as u32;
-    
+    let game = current_game(&state)?;
+
     // Legacy sync endpoint - just return a stub
     // Real streaming happens via submit_action_stream
-    let new_turn = TurnData {
-        turn_number: current_turn,
+    Ok(TurnData {
+        turn_number: game.history().len() as u32,
         story_text: format!("You chose: '{}'. The path unfolds before you, revealing new mysteries and dangers. What will you do next?", action),
         choices: vec![
             "Investigate the strange sound".to_string(),
             "Continue forward cautiously".to_string(),
             "Rest and assess your surroundings".to_string(),
         ],
-        game_state: GameState {
-            time: "Afternoon".to_string(),
-            location: "Enchanted Corridor".to_string(),
-            outfit: "Traveler's Cloak".to_string(),
-        },
-    };
-    
-    history.push(new_turn.clone());
-    Ok(new_turn)
+        game_state: game.state().clone(),
+        usage: None,
+    })
 }
 
 #[tauri::command]
@@ -122,89 +116,49 @@ async fn submit_action_stream(
     action: String,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    println!("🎮 submit_action_stream called with action: {}", action);
-    
-    // Clone what we need from state
-    let turn_number = {
-        let history = state.game_history.lock().unwrap();
-        history.len() as u32
-    };
-
-    println!("📊 Current turn number: {}", turn_number);
-
-    // Clone agent and state to avoid holding locks across await
-    let mut agent = {
-        let agent_guard = state.agent.lock().map_err(|e| e.to_string())?;
-        agent_guard.clone()
-    };
-    
-    let mut current_state = {
-        let state_guard = state.current_game_state.lock().map_err(|e| e.to_string())?;
-        state_guard.clone()
-    };
-
-    // Process the action with streaming - no locks held here
-    println!("🤖 Starting agent.process_action...");
-    let result = agent.process_action(
-        action,
-        &mut current_state,
-        turn_number,
-        |message| {
-            // Emit each message to the frontend
-            println!("📤 Emitting to frontend: {:?}", message);
-            let _ = window.emit("agent-stream", &message);
-            
-            // If it's a turn complete, also save to history
-            if let AgentMessage::TurnComplete { turn_number, story_text, choices, game_state } = &message {
-                if let Ok(mut history) = state.game_history.lock() {
-                    history.push(TurnData {
-                        turn_number: *turn_number,
-                        story_text: story_text.clone(),
-                        choices: choices.clone(),
-                        game_state: GameState {
-                            time: game_state.time.clone(),
-                            location: game_state.location.clone(),
-                            outfit: game_state.outfit.clone(),
-                        },
-                    });
-                }
-            }
-        }
-    ).await;
-
-    // Update the state back after processing
-    if result.is_ok() {
-        println!("✅ Agent processing completed successfully");
-        if let Ok(mut agent_guard) = state.agent.lock() {
-            *agent_guard = agent;
-        }
-        if let Ok(mut state_guard) = state.current_game_state.lock() {
-            *state_guard = current_state;
-        }
-    } else {
-        println!("❌ Agent processing failed: {:?}", result);
+    let mut game = current_game(&state)?;
+    game.take_action(action, |message: AgentMessage| {
+        // Emit each message to the frontend
+        let _ = window.emit("agent-stream", &message);
+    })
+    .await
+    .map_err(|e| {
+        error!(error = %e, "Agent processing failed");
+        e.to_string()
+    })?;
+
+    if let Err(e) = set_game(&state, game).await {
+        error!(error = %e, "Autosave failed");
     }
+    Ok(())
+}
 
-    result.map_err(|e| e.to_string())
+/// Render the exact messages the next action would send to the model
+#[tauri::command]
+async fn preview_prompt(
+    _session_id: String,
+    action: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<ChatMessage>, String> {
+    current_game(&state)?
+        .preview_prompt(&action)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn list_saves() -> Result<Vec<SaveGame>, String> {
-    // Stub: Return mock save games
-    Ok(vec![
-        SaveGame {
-            id: "save_001".to_string(),
-            name: "The Forest Adventure".to_string(),
-            last_played: "2025-10-15".to_string(),
-            turn_count: 23,
-        },
-        SaveGame {
-            id: "save_002".to_string(),
-            name: "Castle Siege".to_string(),
-            last_played: "2025-10-14".to_string(),
-            turn_count: 15,
-        },
-    ])
+fn get_world_map(_session_id: String, state: State<AppState>) -> Result<WorldMap, String> {
+    Ok(current_game(&state)?.world_map())
+}
+
+#[tauri::command]
+fn get_session_stats(_session_id: String, state: State<AppState>) -> Result<SessionStats, String> {
+    Ok(current_game(&state)?.stats())
+}
+
+#[tauri::command]
+async fn list_saves(state: State<'_, AppState>) -> Result<Vec<SaveGame>, String> {
+    Ok(state.engine.list_saves().await)
 }
 
 #[tauri::command]
@@ -220,26 +174,59 @@ fn set_ollama_config(ip_address: String, state: State<AppState>) -> Result<(), S
     Ok(())
 }
 
+/// Bundled scenario packs first, then the player's own packs in <app data>/scenarios,
+/// which may override them. Saves and overrides live in the app data directory too.
+fn engine_config(app: &tauri::App) -> EngineConfig {
+    let mut scenario_roots = Vec::new();
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        scenario_roots.push(resource_dir.join("scenarios"));
+    }
+    let data_dir = app.path().app_data_dir().ok();
+    if let Some(data_dir) = &data_dir {
+        scenario_roots.push(data_dir.join("scenarios"));
+    }
+    EngineConfig {
+        scenario_roots,
+        data_dir,
+        ollama_url: None,
+        model: None,
+        cassette: None,
+        dice_seed: None,
+    }
+}
+
 fn main() {
     tauri::Builder::default()
-        .manage(AppState {
-            ollama_config: Mutex::new(OllamaConfig {
-                ip_address: "192.168.0.100:11434".to_string(),
-            }),
-            game_history: Mutex::new(Vec::new()),
-            agent: Mutex::new(Agent::new()),
-            current_game_state: Mutex::new(AgentGameState {
-                time: "Morning".to_string(),
-                location: "Mysterious Room".to_string(),
-                outfit: "Traveler's Cloak".to_string(),
-            }),
+        .setup(|app| {
+            // Logs go to <app log dir>/luup.<date>.log, and to the console in development
+            let log_guard = logging::init(LogConfig {
+                log_dir: app.path().app_log_dir().ok(),
+                file_name: "luup",
+                stderr: cfg!(debug_assertions),
+                default_filter: "info",
+            })
+            .map_err(|e| e as Box<dyn std::error::Error>)?;
+            app.manage(AppState {
+                ollama_config: Mutex::new(OllamaConfig {
+                    ip_address: DEFAULT_URL.to_string(),
+                }),
+                engine: Engine::new(engine_config(app)),
+                game: Mutex::new(None),
+                _log_guard: log_guard,
+            });
+            Ok(())
         })
         .plugin(tauri_plugin_shell::init())
         .invoke_handler(tauri::generate_handler![
+            list_scenarios,
             start_new_game,
+            load_game,
             get_turn,
             submit_action,
             submit_action_stream,
+            preview_prompt,
+            get_world_map,
+            get_session_stats,
             list_saves,
             get_ollama_config,
             set_ollama_config,
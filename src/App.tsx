@@ -2,6 +2,7 @@ import { BrowserRouter as Router, Routes, Route } from 'react-router-dom';
 import { GameProvider } from './contexts/GameContext';
 import { SettingsProvider } from './contexts/SettingsContext';
 import { HomePage } from './pages/HomePage';
+import { CharacterCreationPage } from './pages/CharacterCreationPage';
 import { SettingsPage } from './pages/SettingsPage';
 import { GamePage } from './pages/GamePage';
 
@@ -12,6 +13,7 @@ function App() {
         <GameProvider>
           <Routes>
             <Route path="/" element={<HomePage />} />
+            <Route path="/new" element={<CharacterCreationPage />} />
             <Route path="/settings" element={<SettingsPage />} />
             <Route path="/game" element={<GamePage />} />
           </Routes>
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrorKind } from "./ErrorKind";
+import type { GameState } from "./GameState";
+import type { JsonValue } from "./serde_json/JsonValue";
+import type { PipelineStage } from "./PipelineStage";
+import type { TurnData } from "./TurnData";
+import type { Usage } from "./Usage";
+
+/**
+ * Messages that can be streamed to the frontend
+ */
+export type AgentMessage = { "type": "text_chunk", content: string, } | { "type": "reasoning_chunk", content: string, } | { "type": "stage_started", stage: PipelineStage, } | { "type": "ruling_chunk", content: string, } | { "type": "dialogue", speaker: string, content: string, } | { "type": "tool_call", name: string, args: JsonValue, } | { "type": "tool_result", name: string, result: GameState, } | { "type": "choices", choices: Array<string>, } | { "type": "usage" } & Usage | { "type": "turn_complete" } & TurnData | { "type": "error", message: string, 
+/**
+ * Structured context, e.g. the offending tool-call arguments
+ */
+details?: JsonValue, 
+/**
+ * Set when talking to Ollama failed
+ */
+kind?: ErrorKind, };
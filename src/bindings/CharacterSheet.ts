@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The player character, created before turn 0
+ */
+export type CharacterSheet = { name: string, pronouns: string, background: string, stats: { [key in string]?: number }, outfit: string, };
@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BackgroundOption } from "./BackgroundOption";
+import type { CharacterSheet } from "./CharacterSheet";
+import type { StatDef } from "./StatDef";
+
+/**
+ * Character creation options offered by a scenario
+ */
+export type CharacterTemplate = { backgrounds: Array<BackgroundOption>, stats: Array<StatDef>, 
+/**
+ * Points the player may spend raising stats above their defaults
+ */
+stat_points: number, outfits: Array<string>, 
+/**
+ * Sheet used when the player skips character creation
+ */
+default: CharacterSheet, };
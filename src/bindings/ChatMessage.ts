@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Chat message for conversation history
+ */
+export type ChatMessage = { role: string, content: string, 
+/**
+ * Native tool calls made by the assistant
+ */
+tool_calls?: Array<JsonValue>, 
+/**
+ * Name of the tool a `tool` role message is answering
+ */
+tool_name?: string, };
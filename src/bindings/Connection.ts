@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A path between two locations, traversable in both directions
+ */
+export type Connection = { from: string, to: string, travel_minutes: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What went wrong talking to Ollama, so the UI can suggest a fix
+ */
+export type ErrorKind = "unreachable" | "model_missing" | "context_overflow" | "timeout" | "server";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * In-game calendar time, counted from day 1 at midnight
+ */
+export type GameClock = { day: number, hour: number, minute: number, time_of_day: string, };
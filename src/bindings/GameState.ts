@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CharacterSheet } from "./CharacterSheet";
+import type { GameClock } from "./GameClock";
+import type { ScheduledEvent } from "./ScheduledEvent";
+import type { VariableStore } from "./VariableStore";
+import type { WorldMap } from "./WorldMap";
+
+/**
+ * Game state shared by the agent, the Tauri commands and save files.
+ * Missing fields fall back to their defaults, so saves from older versions keep loading.
+ * The starting values for a new game come from its scenario pack.
+ */
+export type GameState = { character: CharacterSheet, 
+/**
+ * Older saves stored a plain time-of-day string under `time`
+ */
+clock: GameClock, location: string, outfit: string, world: WorldMap, 
+/**
+ * Events waiting for the clock to reach them
+ */
+scheduled_events: Array<ScheduledEvent>, 
+/**
+ * Events the clock passed this turn, reported to the model on the next one
+ */
+triggered_events: Array<ScheduledEvent>, variables: VariableStore, };
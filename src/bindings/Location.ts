@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A place in the game world
+ */
+export type Location = { name: string, description: string, discovered: boolean, };
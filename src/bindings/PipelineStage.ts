@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A model pass within a turn when the pipeline is enabled
+ */
+export type PipelineStage = "arbiter" | "narrator";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Entry in the list of saved games
+ */
+export type SaveGame = { id: string, name: string, last_played: string, turn_count: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CharacterTemplate } from "./CharacterTemplate";
+
+/**
+ * Short description shown in the scenario picker, with its character creation options
+ */
+export type ScenarioSummary = { id: string, name: string, description: string, genre: string, character: CharacterTemplate, };
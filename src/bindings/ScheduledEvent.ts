@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GameClock } from "./GameClock";
+
+/**
+ * Something the story has scheduled to happen at a given time
+ */
+export type ScheduledEvent = { at: GameClock, description: string, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Usage over a whole session, for the stats panel
+ */
+export type SessionStats = { 
+/**
+ * Turns with recorded usage; the opening and turns from older saves have none
+ */
+turns: number, requests: number, prompt_tokens: number, completion_tokens: number, 
+/**
+ * Tokens generated per second of generation
+ */
+tokens_per_second: number | null, mean_first_token_ms: number | null, 
+/**
+ * Context used by the latest turn and the share of the window it filled, 0 to 1
+ */
+context_tokens: number, context_window: number, context_usage: number | null, 
+/**
+ * Largest share of the window any turn filled
+ */
+peak_context_usage: number | null, };
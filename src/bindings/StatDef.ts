@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A stat the player can raise during character creation
+ */
+export type StatDef = { name: string, description: string, min: number, max: number, default: number, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GameState } from "./GameState";
+import type { Usage } from "./Usage";
+
+/**
+ * One completed turn, as stored in the history and shown to the player
+ */
+export type TurnData = { turn_number: number, story_text: string, choices: Array<string>, game_state: GameState, 
+/**
+ * What the turn's model requests cost; unset for the opening and older saves
+ */
+usage?: Usage, };
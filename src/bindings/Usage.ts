@@ -0,0 +1,39 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Tokens and timings of the storyteller's model requests in one turn, from the
+ * counters Ollama sends with the last chunk of each reply. Durations are in milliseconds.
+ */
+export type Usage = { 
+/**
+ * Model replies received
+ */
+requests: number, 
+/**
+ * Prompt tokens Ollama evaluated
+ */
+prompt_tokens: number, 
+/**
+ * Tokens generated
+ */
+completion_tokens: number, total_ms: number, 
+/**
+ * Time spent loading the model
+ */
+load_ms: number, prompt_eval_ms: number, 
+/**
+ * Time spent generating
+ */
+eval_ms: number, 
+/**
+ * From the player's action to the first streamed chunk
+ */
+first_token_ms?: number, 
+/**
+ * Largest prompt plus reply of any one request
+ */
+context_tokens: number, 
+/**
+ * Context window requested from Ollama
+ */
+context_window: number, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { VarType } from "./VarType";
+import type { VarValue } from "./VarValue";
+
+/**
+ * Declaration of a variable by the scenario author
+ */
+export type VarDecl = { name: string, type: VarType, description: string, initial: VarValue | null, };
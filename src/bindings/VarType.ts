@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Type of a story variable
+ */
+export type VarType = "bool" | "int" | "float" | "string";
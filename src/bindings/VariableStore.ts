@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { VarDecl } from "./VarDecl";
+import type { VarValue } from "./VarValue";
+
+/**
+ * Typed key/value store for arbitrary story state
+ */
+export type VariableStore = { schema: Array<VarDecl>, values: { [key in string]?: VarValue }, };
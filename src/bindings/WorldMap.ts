@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Connection } from "./Connection";
+import type { Location } from "./Location";
+
+/**
+ * Graph of locations and the paths between them
+ */
+export type WorldMap = { locations: Array<Location>, connections: Array<Connection>, };
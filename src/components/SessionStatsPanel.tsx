@@ -0,0 +1,30 @@
+import React from 'react';
+import type { SessionStats } from '../services/backend';
+
+interface SessionStatsPanelProps {
+  stats: SessionStats | null;
+}
+
+const percent = (share: number) => `${Math.round(share * 100)}%`;
+
+// One line of the storyteller's token use and speed over the session
+export const SessionStatsPanel: React.FC<SessionStatsPanelProps> = ({ stats }) => {
+  if (!stats || stats.turns === 0) return null;
+
+  const parts = [
+    `${stats.turns} ${stats.turns === 1 ? 'turn' : 'turns'}`,
+    `${(stats.prompt_tokens + stats.completion_tokens).toLocaleString()} tokens`,
+  ];
+  if (stats.tokens_per_second !== null) parts.push(`${stats.tokens_per_second.toFixed(1)} tokens/s`);
+  if (stats.mean_first_token_ms !== null) parts.push(`first token ${(stats.mean_first_token_ms / 1000).toFixed(1)} s`);
+  if (stats.context_usage !== null) {
+    const peak = stats.peak_context_usage !== null ? ` (peak ${percent(stats.peak_context_usage)})` : '';
+    parts.push(`context ${percent(stats.context_usage)} of ${stats.context_window.toLocaleString()}${peak}`);
+  }
+
+  return (
+    <div className="mb-6 px-4 text-xs text-slate-500" title="Storyteller usage this session">
+      {parts.join(' · ')}
+    </div>
+  );
+};
@@ -15,7 +15,8 @@ export const TurnDisplay: React.FC<TurnDisplayProps> = ({ storyText, gameState }
           📍 {gameState.location}
         </span>
         <span className="px-4 py-2 bg-glass-base backdrop-blur-glass rounded-full text-sm text-slate-300 border border-white/10">
-          🕐 {gameState.time}
+          🕐 Day {gameState.clock.day}, {String(gameState.clock.hour).padStart(2, '0')}:
+          {String(gameState.clock.minute).padStart(2, '0')} · {gameState.clock.time_of_day}
         </span>
         <span className="px-4 py-2 bg-glass-base backdrop-blur-glass rounded-full text-sm text-slate-300 border border-white/10">
           👔 {gameState.outfit}
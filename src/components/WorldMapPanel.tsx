@@ -0,0 +1,56 @@
+import React, { useState } from 'react';
+import type { WorldMap } from '../services/backend';
+
+interface WorldMapPanelProps {
+  map: WorldMap | null;
+  currentLocation: string;
+}
+
+// Location names match regardless of case, as in the engine
+const same = (a: string, b: string) => a.toLowerCase() === b.toLowerCase();
+
+// Discovered locations with the routes out of each; the player's location comes first
+export const WorldMapPanel: React.FC<WorldMapPanelProps> = ({ map, currentLocation }) => {
+  const [expanded, setExpanded] = useState(false);
+  if (!map || map.locations.length === 0) return null;
+
+  const locations = [...map.locations].sort(
+    (a, b) => Number(same(b.name, currentLocation)) - Number(same(a.name, currentLocation))
+  );
+  const routesFrom = (name: string) =>
+    map.connections
+      .filter((c) => same(c.from, name) || same(c.to, name))
+      .map((c) => ({ to: same(c.from, name) ? c.to : c.from, minutes: c.travel_minutes }));
+
+  return (
+    <div className="mb-6 glass-card p-4 border border-emerald-500/30">
+      <button
+        onClick={() => setExpanded(!expanded)}
+        className="flex items-center gap-2 text-sm text-emerald-300 hover:text-emerald-200 w-full"
+      >
+        <span className={`transform transition-transform ${expanded ? 'rotate-90' : ''}`}>▶</span>
+        <span>Map ({map.locations.length} places known)</span>
+      </button>
+      {expanded && (
+        <ul className="mt-3 space-y-3">
+          {locations.map((location) => (
+            <li key={location.name} className="text-sm">
+              <div className={same(location.name, currentLocation) ? 'text-emerald-300 font-semibold' : 'text-slate-200'}>
+                {same(location.name, currentLocation) ? '📍 ' : ''}
+                {location.name}
+              </div>
+              {location.description && <div className="text-slate-400">{location.description}</div>}
+              {routesFrom(location.name).length > 0 && (
+                <div className="text-slate-500">
+                  {routesFrom(location.name)
+                    .map((route) => `→ ${route.to} (${route.minutes} min)`)
+                    .join(' · ')}
+                </div>
+              )}
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+};
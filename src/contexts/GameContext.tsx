@@ -9,6 +9,7 @@ interface GameContextType {
   currentTurnIndex: number;
   setSessionId: (id: string) => void;
   addTurn: (turn: TurnData) => void;
+  loadTurns: (turns: TurnData[]) => void;
   navigateToTurn: (index: number) => void;
 }
 
@@ -24,6 +25,12 @@ export const GameProvider: React.FC<{ children: ReactNode }> = ({ children }) =>
     setCurrentTurnIndex(turnHistory.length);
   };
 
+  // Replace the whole history, e.g. when resuming a saved game
+  const loadTurns = (turns: TurnData[]) => {
+    setTurnHistory(turns);
+    setCurrentTurnIndex(Math.max(turns.length - 1, 0));
+  };
+
   const navigateToTurn = (index: number) => {
     if (index >= 0 && index < turnHistory.length) {
       setCurrentTurnIndex(index);
@@ -41,6 +48,7 @@ export const GameProvider: React.FC<{ children: ReactNode }> = ({ children }) =>
         currentTurnIndex,
         setSessionId,
         addTurn,
+        loadTurns,
         navigateToTurn,
       }}
     >
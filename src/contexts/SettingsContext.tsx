@@ -5,7 +5,7 @@ import { backend } from '../services/backend';
 interface SettingsContextType {
   ollamaIp: string;
   setOllamaIp: (ip: string) => void;
-  saveSettings: () => Promise<void>;
+  saveSettings: (ip: string, adminToken?: string) => Promise<void>;
 }
 
 const SettingsContext = createContext<SettingsContextType | undefined>(undefined);
@@ -17,8 +17,9 @@ export const SettingsProvider: React.FC<{ children: ReactNode }> = ({ children }
     backend.getOllamaConfig().then((config) => setOllamaIp(config.ip_address));
   }, []);
 
-  const saveSettings = async () => {
-    await backend.setOllamaConfig(ollamaIp);
+  const saveSettings = async (ip: string, adminToken?: string) => {
+    await backend.setOllamaConfig(ip, adminToken);
+    setOllamaIp(ip);
   };
 
   return (
@@ -0,0 +1,210 @@
+import React, { useState } from 'react';
+import { useLocation, useNavigate } from 'react-router-dom';
+import { backend } from '../services/backend';
+import type { CharacterSheet, ScenarioSummary } from '../services/backend';
+import { useGame } from '../contexts/GameContext';
+
+const PRONOUN_OPTIONS = ['she/her', 'he/him', 'they/them'];
+
+const inputClass =
+  'w-full bg-glass-base backdrop-blur-glass rounded-xl border border-white/10 p-4 text-slate-50 placeholder-slate-500 focus:outline-none focus:border-blue-500/50 focus:ring-2 focus:ring-blue-500/20';
+
+export const CharacterCreationPage: React.FC = () => {
+  const navigate = useNavigate();
+  const location = useLocation();
+  const { setSessionId, loadTurns } = useGame();
+  const scenario = (location.state as { scenario?: ScenarioSummary } | null)?.scenario;
+  const template = scenario?.character;
+
+  const [sheet, setSheet] = useState<CharacterSheet>(() => ({
+    name: '',
+    pronouns: template?.default.pronouns || 'they/them',
+    background: template?.default.background || '',
+    // Start every stat at its default; points are spent from there
+    stats: Object.fromEntries((template?.stats ?? []).map((stat) => [stat.name, stat.default])),
+    outfit: template?.default.outfit || template?.outfits[0] || '',
+  }));
+  const [starting, setStarting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  if (!scenario || !template) {
+    return (
+      <div className="min-h-screen flex items-center justify-center">
+        <button
+          onClick={() => navigate('/')}
+          className="glass-button px-6 py-3 text-slate-300 hover:text-slate-50"
+        >
+          Choose a scenario first
+        </button>
+      </div>
+    );
+  }
+
+  const pointsSpent = template.stats.reduce(
+    (total, stat) => total + ((sheet.stats[stat.name] ?? stat.default) - stat.default),
+    0
+  );
+  const pointsLeft = template.stat_points - pointsSpent;
+
+  const setStat = (name: string, value: number) =>
+    setSheet((prev) => ({ ...prev, stats: { ...prev.stats, [name]: value } }));
+
+  const startGame = async (character: CharacterSheet | undefined) => {
+    setStarting(true);
+    setError(null);
+    try {
+      const sessionId = await backend.startNewGame(scenario.id, character);
+      setSessionId(sessionId);
+      loadTurns([await backend.getTurn(sessionId, 0)]);
+      navigate('/game');
+    } catch (e) {
+      console.error('Failed to start new game:', e);
+      setError(String(e));
+      setStarting(false);
+    }
+  };
+
+  return (
+    <div className="min-h-screen flex items-center justify-center p-8">
+      <div className="max-w-2xl w-full">
+        <button
+          onClick={() => navigate('/')}
+          className="glass-button px-4 py-2 mb-8 flex items-center gap-2 text-slate-300 hover:text-slate-50"
+        >
+          <span>←</span>
+          <span>Back</span>
+        </button>
+
+        <div className="glass-card p-8">
+          <h1 className="text-4xl font-heading font-bold mb-2 text-slate-50">Who are you?</h1>
+          <p className="text-slate-400 mb-8">{scenario.name}</p>
+
+          {/* Name and pronouns */}
+          <div className="mb-6">
+            <label className="block text-sm font-medium text-slate-300 mb-3">Name</label>
+            <input
+              type="text"
+              value={sheet.name}
+              onChange={(e) => setSheet({ ...sheet, name: e.target.value })}
+              placeholder={template.default.name || 'Your name'}
+              className={inputClass}
+            />
+          </div>
+
+          <div className="mb-6">
+            <label className="block text-sm font-medium text-slate-300 mb-3">Pronouns</label>
+            <div className="flex gap-3">
+              {PRONOUN_OPTIONS.map((option) => (
+                <button
+                  key={option}
+                  onClick={() => setSheet({ ...sheet, pronouns: option })}
+                  className={`glass-button px-4 py-2 ${sheet.pronouns === option ? 'text-slate-50 border-blue-500/50' : 'text-slate-400'}`}
+                >
+                  {option}
+                </button>
+              ))}
+              <input
+                type="text"
+                value={PRONOUN_OPTIONS.includes(sheet.pronouns) ? '' : sheet.pronouns}
+                onChange={(e) => setSheet({ ...sheet, pronouns: e.target.value })}
+                placeholder="Other"
+                className={`${inputClass} py-2`}
+              />
+            </div>
+          </div>
+
+          {/* Background */}
+          <div className="mb-6">
+            <label className="block text-sm font-medium text-slate-300 mb-3">Background</label>
+            <div className="grid gap-3">
+              {template.backgrounds.map((background) => (
+                <button
+                  key={background.name}
+                  onClick={() => setSheet({ ...sheet, background: background.name })}
+                  className={`glass-button p-4 text-left ${sheet.background === background.name ? 'border-blue-500/50' : ''}`}
+                >
+                  <h4 className="font-semibold text-slate-50">{background.name}</h4>
+                  <p className="text-sm text-slate-400">{background.description}</p>
+                </button>
+              ))}
+            </div>
+          </div>
+
+          {/* Stats */}
+          {template.stats.length > 0 && (
+            <div className="mb-6">
+              <label className="block text-sm font-medium text-slate-300 mb-3">
+                Stats · {pointsLeft} point{pointsLeft === 1 ? '' : 's'} left
+              </label>
+              <div className="space-y-3">
+                {template.stats.map((stat) => {
+                  const value = sheet.stats[stat.name] ?? stat.default;
+                  return (
+                    <div key={stat.name} className="flex items-center justify-between gap-4">
+                      <div>
+                        <h4 className="font-semibold text-slate-50 capitalize">{stat.name}</h4>
+                        <p className="text-sm text-slate-400">{stat.description}</p>
+                      </div>
+                      <div className="flex items-center gap-3">
+                        <button
+                          onClick={() => setStat(stat.name, value - 1)}
+                          disabled={value <= stat.min}
+                          className="glass-button w-8 h-8 text-slate-300 disabled:opacity-30"
+                        >
+                          −
+                        </button>
+                        <span className="w-6 text-center text-slate-50 font-semibold">{value}</span>
+                        <button
+                          onClick={() => setStat(stat.name, value + 1)}
+                          disabled={value >= stat.max || pointsLeft <= 0}
+                          className="glass-button w-8 h-8 text-slate-300 disabled:opacity-30"
+                        >
+                          +
+                        </button>
+                      </div>
+                    </div>
+                  );
+                })}
+              </div>
+            </div>
+          )}
+
+          {/* Outfit */}
+          {template.outfits.length > 0 && (
+            <div className="mb-8">
+              <label className="block text-sm font-medium text-slate-300 mb-3">Starting Outfit</label>
+              <select
+                value={sheet.outfit}
+                onChange={(e) => setSheet({ ...sheet, outfit: e.target.value })}
+                className={inputClass}
+              >
+                {template.outfits.map((outfit) => (
+                  <option key={outfit} value={outfit}>
+                    {outfit}
+                  </option>
+                ))}
+              </select>
+            </div>
+          )}
+
+          {error && <p className="text-sm text-red-400 mb-4">{error}</p>}
+
+          <button
+            onClick={() => startGame(sheet)}
+            disabled={starting || !sheet.name.trim()}
+            className="w-full px-6 py-3 mb-3 bg-gradient-to-r from-blue-500 to-indigo-500 hover:from-blue-600 hover:to-indigo-600 rounded-xl font-semibold text-white transition-all shadow-lg shadow-blue-500/20 disabled:opacity-50"
+          >
+            {starting ? 'Starting...' : 'Begin Adventure'}
+          </button>
+          <button
+            onClick={() => startGame(undefined)}
+            disabled={starting}
+            className="w-full glass-button p-3 text-slate-300 hover:text-slate-50 disabled:opacity-50"
+          >
+            Skip and play as {template.default.name || 'the default character'}
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+};
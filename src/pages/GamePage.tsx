@@ -1,12 +1,29 @@
 import React, { useState, useEffect } from 'react';
 import { useNavigate } from 'react-router-dom';
-import { listen } from '@tauri-apps/api/event';
 import { useGame } from '../contexts/GameContext';
-import { backend, type AgentStreamMessage } from '../services/backend';
+import {
+  backend,
+  type AgentStreamMessage,
+  type ErrorKind,
+  type PipelineStage,
+  type SessionStats,
+  type WorldMap,
+} from '../services/backend';
 import { TurnDisplay } from '../components/TurnDisplay';
 import { ChoiceButton } from '../components/ChoiceButton';
 import { ActionInput } from '../components/ActionInput';
 import { TurnNavigation } from '../components/TurnNavigation';
+import { WorldMapPanel } from '../components/WorldMapPanel';
+import { SessionStatsPanel } from '../components/SessionStatsPanel';
+
+// What the player can do about each way talking to Ollama can fail
+const ERROR_HINTS: Record<ErrorKind, string> = {
+  unreachable: 'Check that Ollama is running and its address in Settings is right.',
+  model_missing: 'Pull the model on the Ollama server with `ollama pull`.',
+  context_overflow: "The story no longer fits the model's context window; raise its context budget.",
+  timeout: 'Ollama took too long; it may still be loading the model, so try again.',
+  server: 'Ollama reported an error; its log has the details.',
+};
 
 export const GamePage: React.FC = () => {
   const navigate = useNavigate();
@@ -20,16 +37,24 @@ export const GamePage: React.FC = () => {
   const [streamedText, setStreamedText] = useState('');
   const [streamedReasoning, setStreamedReasoning] = useState('');
   const [showReasoning, setShowReasoning] = useState(false);
+  // Set when turns run through the arbiter/narrator pipeline
+  const [stage, setStage] = useState<PipelineStage | null>(null);
+  const [streamedRuling, setStreamedRuling] = useState('');
+  const [showRuling, setShowRuling] = useState(false);
+  const [dialogue, setDialogue] = useState<Array<{ speaker: string; content: string }>>([]);
   const [toolCallsInProgress, setToolCallsInProgress] = useState<Array<{ name: string; args: any }>>([]);
   const [streamError, setStreamError] = useState<string | null>(null);
+  const [worldMap, setWorldMap] = useState<WorldMap | null>(null);
+  const [sessionStats, setSessionStats] = useState<SessionStats | null>(null);
 
   const isCurrentTurn = currentTurnIndex === turnHistory.length - 1;
 
   // Set up streaming event listener
   useEffect(() => {
+    if (!sessionId) return;
+
     const setupListener = async () => {
-      const unlisten = await listen<AgentStreamMessage>('agent-stream', (event) => {
-        const message = event.payload;
+      const unlisten = await backend.onAgentStream(sessionId, (message: AgentStreamMessage) => {
         console.log('📥 Frontend received message:', message);
 
         switch (message.type) {
@@ -43,6 +68,26 @@ export const GamePage: React.FC = () => {
             setStreamedReasoning((prev) => prev + message.content);
             break;
 
+          case 'stage_started':
+            console.log('🎬 Stage started:', message.stage);
+            setStage(message.stage);
+            break;
+
+          case 'ruling_chunk':
+            setStreamedRuling((prev) => prev + message.content);
+            break;
+
+          case 'dialogue':
+            // Consecutive chunks from the same speaker make up one line
+            setDialogue((prev) => {
+              const last = prev[prev.length - 1];
+              if (last && last.speaker === message.speaker) {
+                return [...prev.slice(0, -1), { ...last, content: last.content + message.content }];
+              }
+              return [...prev, { speaker: message.speaker, content: message.content }];
+            });
+            break;
+
           case 'tool_call':
             console.log('🔧 Tool call:', message.name);
             setToolCallsInProgress((prev) => [...prev, { name: message.name, args: message.args }]);
@@ -67,13 +112,25 @@ export const GamePage: React.FC = () => {
             setIsSubmitting(false);
             setStreamedText('');
             setStreamedReasoning('');
+            setStreamedRuling('');
+            setStage(null);
+            setDialogue([]);
             setToolCallsInProgress([]);
             setCustomAction('');
             break;
 
+          case 'usage':
+            // Also saved on the turn; getSessionStats sums it over the session
+            break;
+
           case 'error':
+            if (message.details) {
+              // Rejected tool calls are reported back to the model, which keeps going
+              console.warn('⚠️ Tool error:', message.message, message.details);
+              break;
+            }
             console.error('❌ Error message:', message.message);
-            setStreamError(message.message);
+            setStreamError(message.kind ? `${message.message}. ${ERROR_HINTS[message.kind]}` : message.message);
             setIsStreaming(false);
             setIsSubmitting(false);
             break;
@@ -94,7 +151,25 @@ export const GamePage: React.FC = () => {
     return () => {
       if (unlistenFn) unlistenFn();
     };
-  }, [addTurn]);
+  }, [sessionId, addTurn]);
+
+  // The map only grows, so it is fetched again whenever a turn is added
+  useEffect(() => {
+    if (!sessionId) return;
+    backend
+      .getWorldMap(sessionId)
+      .then(setWorldMap)
+      .catch((error) => console.warn('⚠️ Failed to load the world map:', error));
+  }, [sessionId, turnHistory.length]);
+
+  // Every completed turn adds to the session's usage
+  useEffect(() => {
+    if (!sessionId) return;
+    backend
+      .getSessionStats(sessionId)
+      .then(setSessionStats)
+      .catch((error) => console.warn('⚠️ Failed to load the session stats:', error));
+  }, [sessionId, turnHistory.length]);
 
   const handleChoiceClick = (choice: string) => {
     setCustomAction(choice);
@@ -111,6 +186,9 @@ export const GamePage: React.FC = () => {
     setIsStreaming(true);
     setStreamedText('');
     setStreamedReasoning('');
+    setStreamedRuling('');
+    setStage(null);
+    setDialogue([]);
     setStreamError(null);
 
     try {
@@ -119,7 +197,10 @@ export const GamePage: React.FC = () => {
       console.log('✅ Backend call completed');
     } catch (error) {
       console.error('❌ Failed to submit action:', error);
-      setStreamError('Failed to connect to the storyteller. Please check your Ollama connection.');
+      // Keep the typed error the stream may already have reported, with its hint
+      setStreamError(
+        (current) => current ?? 'Failed to connect to the storyteller. Please check your Ollama connection.'
+      );
       setIsStreaming(false);
       setIsSubmitting(false);
     }
@@ -154,7 +235,7 @@ export const GamePage: React.FC = () => {
             <span>Home</span>
           </button>
           <h1 className="text-2xl font-heading font-semibold text-slate-50">
-            Your Adventure
+            {currentTurn.game_state.character.name || 'Your Adventure'}
           </h1>
           <div className="w-24"></div> {/* Spacer for centering */}
         </div>
@@ -162,6 +243,12 @@ export const GamePage: React.FC = () => {
         {/* Story Display */}
         <TurnDisplay storyText={currentTurn.story_text} gameState={currentTurn.game_state} />
 
+        {/* World Map */}
+        <WorldMapPanel map={worldMap} currentLocation={currentTurn.game_state.location} />
+
+        {/* Session Stats */}
+        <SessionStatsPanel stats={sessionStats} />
+
         {/* Streaming Content */}
         {isStreaming && (
           <div className="mb-6 space-y-4">
@@ -169,9 +256,22 @@ export const GamePage: React.FC = () => {
             <div className="glass-card p-4 border border-blue-500/30">
               <div className="flex items-center gap-3 mb-4">
                 <div className="animate-pulse w-2 h-2 bg-blue-500 rounded-full"></div>
-                <span className="text-slate-300 text-sm">The tale unfolds...</span>
+                <span className="text-slate-300 text-sm">
+                  {stage === 'arbiter' ? 'The rules are weighed...' : 'The tale unfolds...'}
+                </span>
               </div>
 
+              {/* NPC Dialogue */}
+              {dialogue.length > 0 && (
+                <div className="mb-4 space-y-2">
+                  {dialogue.map((line, idx) => (
+                    <p key={idx} className="text-slate-300 italic">
+                      <span className="not-italic font-semibold text-emerald-300">{line.speaker}:</span> "{line.content}"
+                    </p>
+                  ))}
+                </div>
+              )}
+
               {/* Streamed Text */}
               {streamedText && (
                 <div className="prose prose-invert max-w-none">
@@ -192,6 +292,26 @@ export const GamePage: React.FC = () => {
               )}
             </div>
 
+            {/* Arbiter Ruling */}
+            {streamedRuling && (
+              <div className="glass-card p-4 border border-amber-500/30">
+                <button
+                  onClick={() => setShowRuling(!showRuling)}
+                  className="flex items-center gap-2 text-sm text-amber-300 hover:text-amber-200 w-full"
+                >
+                  <span className={`transform transition-transform ${showRuling ? 'rotate-90' : ''}`}>
+                    ▶
+                  </span>
+                  <span>Arbiter Ruling</span>
+                </button>
+                {showRuling && (
+                  <div className="mt-3 text-sm text-slate-400 whitespace-pre-wrap font-mono">
+                    {streamedRuling}
+                  </div>
+                )}
+              </div>
+            )}
+
             {/* Reasoning Display */}
             {streamedReasoning && (
               <div className="glass-card p-4 border border-purple-500/30">
@@ -1,31 +1,35 @@
 import React, { useEffect, useState } from 'react';
 import { useNavigate } from 'react-router-dom';
 import { backend } from '../services/backend';
-import type { SaveGame } from '../services/backend';
+import type { SaveGame, ScenarioSummary } from '../services/backend';
 import { useGame } from '../contexts/GameContext';
 
 export const HomePage: React.FC = () => {
   const navigate = useNavigate();
-  const { setSessionId, addTurn } = useGame();
+  const { setSessionId, loadTurns } = useGame();
   const [saves, setSaves] = useState<SaveGame[]>([]);
-  const [loading, setLoading] = useState(false);
+  const [scenarios, setScenarios] = useState<ScenarioSummary[]>([]);
+  const [loading, setLoading] = useState<string | null>(null);
 
   useEffect(() => {
     backend.listSaves().then(setSaves);
+    backend.listScenarios().then(setScenarios);
   }, []);
 
-  const handleNewGame = async () => {
-    setLoading(true);
+  const handleLoadGame = async (save: SaveGame) => {
+    setLoading(save.id);
     try {
-      const sessionId = await backend.startNewGame();
+      const sessionId = await backend.loadGame(save.id);
       setSessionId(sessionId);
-      const initialTurn = await backend.getTurn(sessionId, 0);
-      addTurn(initialTurn);
+      const turns = await Promise.all(
+        Array.from({ length: save.turn_count }, (_, i) => backend.getTurn(sessionId, i))
+      );
+      loadTurns(turns);
       navigate('/game');
     } catch (error) {
-      console.error('Failed to start new game:', error);
+      console.error('Failed to load game:', error);
     } finally {
-      setLoading(false);
+      setLoading(null);
     }
   };
 
@@ -40,24 +44,31 @@ export const HomePage: React.FC = () => {
           <p className="text-slate-300 text-lg">Your story begins here</p>
         </div>
 
-        {/* New Game Button */}
-        <button
-          onClick={handleNewGame}
-          disabled={loading}
-          className="w-full glass-card p-8 mb-6 hover:bg-glass-hover transition-all group disabled:opacity-50"
-        >
-          <div className="flex items-center justify-between">
-            <div>
-              <h2 className="text-2xl font-heading font-semibold mb-2 text-slate-50">
-                {loading ? 'Starting...' : 'Start New Adventure'}
-              </h2>
-              <p className="text-slate-400">Begin a fresh journey</p>
-            </div>
-            <span className="text-4xl group-hover:translate-x-2 transition-transform">
-              ✨
-            </span>
+        {/* New Game: one card per scenario pack */}
+        <div className="glass-card p-6 mb-6">
+          <h2 className="text-2xl font-heading font-semibold mb-4 text-slate-50">
+            Start New Adventure
+          </h2>
+          <div className="space-y-3">
+            {scenarios.map((scenario) => (
+              <button
+                key={scenario.id}
+                onClick={() => navigate('/new', { state: { scenario } })}
+                disabled={loading !== null}
+                className="w-full glass-button p-4 text-left hover:bg-glass-hover transition-all group disabled:opacity-50"
+              >
+                <div className="flex justify-between items-start">
+                  <div>
+                    <h4 className="font-semibold text-slate-50">{scenario.name}</h4>
+                    <p className="text-xs uppercase tracking-wide text-blue-400 mb-1">{scenario.genre}</p>
+                    <p className="text-sm text-slate-400">{scenario.description}</p>
+                  </div>
+                  <span className="text-2xl group-hover:translate-x-2 transition-transform">✨</span>
+                </div>
+              </button>
+            ))}
           </div>
-        </button>
+        </div>
 
         {/* Saved Games */}
         <div className="glass-card p-6 mb-6">
@@ -68,15 +79,15 @@ export const HomePage: React.FC = () => {
             {saves.map((save) => (
               <button
                 key={save.id}
-                onClick={() => {
-                  // Stub: Just navigate to game page
-                  navigate('/game');
-                }}
-                className="w-full glass-button p-4 text-left hover:bg-glass-hover transition-all"
+                onClick={() => handleLoadGame(save)}
+                disabled={loading !== null}
+                className="w-full glass-button p-4 text-left hover:bg-glass-hover transition-all disabled:opacity-50"
               >
                 <div className="flex justify-between items-start">
                   <div>
-                    <h4 className="font-semibold text-slate-50">{save.name}</h4>
+                    <h4 className="font-semibold text-slate-50">
+                      {loading === save.id ? 'Loading...' : save.name}
+                    </h4>
                     <p className="text-sm text-slate-400">
                       {save.turn_count} turns • Last played {save.last_played}
                     </p>
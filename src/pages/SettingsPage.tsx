@@ -1,18 +1,25 @@
 import React, { useState } from 'react';
 import { useNavigate } from 'react-router-dom';
 import { useSettings } from '../contexts/SettingsContext';
+import { usesServer } from '../services/backend';
 
 export const SettingsPage: React.FC = () => {
   const navigate = useNavigate();
-  const { ollamaIp, setOllamaIp, saveSettings } = useSettings();
+  const { ollamaIp, saveSettings } = useSettings();
   const [localIp, setLocalIp] = useState(ollamaIp);
+  const [adminToken, setAdminToken] = useState('');
   const [saved, setSaved] = useState(false);
+  const [error, setError] = useState<string | null>(null);
 
   const handleSave = async () => {
-    setOllamaIp(localIp);
-    await saveSettings();
-    setSaved(true);
-    setTimeout(() => setSaved(false), 2000);
+    try {
+      await saveSettings(localIp, adminToken || undefined);
+      setError(null);
+      setSaved(true);
+      setTimeout(() => setSaved(false), 2000);
+    } catch (e) {
+      setError(String(e));
+    }
   };
 
   return (
@@ -49,6 +56,26 @@ export const SettingsPage: React.FC = () => {
             </p>
           </div>
 
+          {/* The server only accepts changes from its admin */}
+          {usesServer && (
+            <div className="mb-6">
+              <label className="block text-sm font-medium text-slate-300 mb-3">
+                Admin Token
+              </label>
+              <input
+                type="password"
+                value={adminToken}
+                onChange={(e) => setAdminToken(e.target.value)}
+                className="w-full bg-glass-base backdrop-blur-glass rounded-xl border border-white/10 p-4 text-slate-50 placeholder-slate-500 focus:outline-none focus:border-blue-500/50 focus:ring-2 focus:ring-blue-500/20"
+              />
+              <p className="text-xs text-slate-400 mt-2">
+                The token luup-server was started with (--admin-token)
+              </p>
+            </div>
+          )}
+
+          {error && <p className="text-sm text-red-300 mb-4">{error}</p>}
+
           {/* Save Button */}
           <button
             onClick={handleSave}
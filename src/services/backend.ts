@@ -1,65 +1,184 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import type { AgentMessage } from '../bindings/AgentMessage';
+import type { CharacterSheet } from '../bindings/CharacterSheet';
+import type { ChatMessage } from '../bindings/ChatMessage';
+import type { SaveGame } from '../bindings/SaveGame';
+import type { ScenarioSummary } from '../bindings/ScenarioSummary';
+import type { SessionStats } from '../bindings/SessionStats';
+import type { TurnData } from '../bindings/TurnData';
+import type { WorldMap } from '../bindings/WorldMap';
 
-export interface TurnData {
-  turn_number: number;
-  story_text: string;
-  choices: string[];
-  game_state: GameState;
-}
-
-export interface GameState {
-  time: string;
-  location: string;
-  outfit: string;
-}
-
-export interface SaveGame {
-  id: string;
-  name: string;
-  last_played: string;
-  turn_count: number;
-}
+// Types shared with the Rust backend are generated by ts-rs (`cargo test -p luup-core`)
+export type { AgentMessage } from '../bindings/AgentMessage';
+export type { CharacterSheet } from '../bindings/CharacterSheet';
+export type { CharacterTemplate } from '../bindings/CharacterTemplate';
+export type { ChatMessage } from '../bindings/ChatMessage';
+export type { ErrorKind } from '../bindings/ErrorKind';
+export type { GameClock } from '../bindings/GameClock';
+export type { GameState } from '../bindings/GameState';
+export type { PipelineStage } from '../bindings/PipelineStage';
+export type { SaveGame } from '../bindings/SaveGame';
+export type { ScenarioSummary } from '../bindings/ScenarioSummary';
+export type { SessionStats } from '../bindings/SessionStats';
+export type { TurnData } from '../bindings/TurnData';
+export type { Usage } from '../bindings/Usage';
+export type { VariableStore } from '../bindings/VariableStore';
+export type { WorldMap } from '../bindings/WorldMap';
 
 export interface OllamaConfig {
   ip_address: string;
 }
 
 // Stream message types from the agent
-export type AgentStreamMessage =
-  | { type: 'text_chunk'; content: string }
-  | { type: 'reasoning_chunk'; content: string }
-  | { type: 'tool_call'; name: string; args: any }
-  | { type: 'tool_result'; name: string; result: GameState }
-  | { type: 'choices'; choices: string[] }
-  | { type: 'turn_complete'; turn_number: number; story_text: string; choices: string[]; game_state: GameState }
-  | { type: 'error'; message: string };
+export type AgentStreamMessage = AgentMessage;
+
+// Inside the desktop app commands go through Tauri. In a browser they go to luup-server
+// over HTTP and turns stream over a WebSocket. VITE_LUUP_TRANSPORT=tauri|http forces one.
+type Transport = 'tauri' | 'http';
+const transport: Transport =
+  (import.meta.env.VITE_LUUP_TRANSPORT as Transport | undefined) ??
+  ('__TAURI_INTERNALS__' in window ? 'tauri' : 'http');
+
+// Talking to luup-server, where changing settings needs its admin token
+export const usesServer = transport === 'http';
+
+type Args = Record<string, any>;
+type Route = { method: 'GET' | 'POST' | 'PUT'; path: string; body?: unknown; headers?: Record<string, string> };
+
+const id = (value: string) => encodeURIComponent(value);
+
+// luup-server's REST endpoint for each command
+const routes: Record<string, (args: Args) => Route> = {
+  list_scenarios: () => ({ method: 'GET', path: '/scenarios' }),
+  start_new_game: ({ scenarioId, character }) => ({
+    method: 'POST',
+    path: '/sessions',
+    body: { scenario_id: scenarioId ?? null, character: character ?? null },
+  }),
+  load_game: ({ saveId }) => ({ method: 'POST', path: `/saves/${id(saveId)}/load` }),
+  get_turn: ({ sessionId, turnNumber }) => ({
+    method: 'GET',
+    path: `/sessions/${id(sessionId)}/turns/${turnNumber}`,
+  }),
+  submit_action_stream: ({ sessionId, action }) => ({
+    method: 'POST',
+    path: `/sessions/${id(sessionId)}/actions`,
+    body: { action },
+  }),
+  preview_prompt: ({ sessionId, action }) => ({
+    method: 'POST',
+    path: `/sessions/${id(sessionId)}/preview`,
+    body: { action },
+  }),
+  get_world_map: ({ sessionId }) => ({ method: 'GET', path: `/sessions/${id(sessionId)}/map` }),
+  get_session_stats: ({ sessionId }) => ({ method: 'GET', path: `/sessions/${id(sessionId)}/stats` }),
+  list_saves: () => ({ method: 'GET', path: '/saves' }),
+  get_ollama_config: () => ({ method: 'GET', path: '/config' }),
+  set_ollama_config: ({ ipAddress, adminToken }) => ({
+    method: 'PUT',
+    path: '/config',
+    body: { ip_address: ipAddress },
+    headers: adminToken ? { Authorization: `Bearer ${adminToken}` } : undefined,
+  }),
+};
+
+async function call<T>(command: string, args: Args = {}): Promise<T> {
+  if (transport === 'tauri') {
+    return await invoke<T>(command, args);
+  }
+  const route = routes[command];
+  if (!route) {
+    throw `${command} is not available in the browser`;
+  }
+  const { method, path, body, headers } = route(args);
+  const response = await fetch(`/api${path}`, {
+    method,
+    headers: { ...(body === undefined ? {} : { 'Content-Type': 'application/json' }), ...headers },
+    body: body === undefined ? undefined : JSON.stringify(body),
+  });
+  if (!response.ok) {
+    // Tauri commands reject with the error message too
+    throw await response.text();
+  }
+  return (await response.json()) as T;
+}
+
+// Reconnects when the socket drops, e.g. while a phone sleeps
+function streamOverWebSocket(sessionId: string, handler: (message: AgentMessage) => void): () => void {
+  const protocol = window.location.protocol === 'https:' ? 'wss:' : 'ws:';
+  const url = `${protocol}//${window.location.host}/api/sessions/${id(sessionId)}/events`;
+  let socket: WebSocket;
+  let closed = false;
+  const connect = () => {
+    socket = new WebSocket(url);
+    socket.onmessage = (event) => handler(JSON.parse(event.data));
+    socket.onclose = () => {
+      if (!closed) setTimeout(connect, 1000);
+    };
+  };
+  connect();
+  return () => {
+    closed = true;
+    socket.close();
+  };
+}
 
 export const backend = {
-  async startNewGame(): Promise<string> {
-    return await invoke('start_new_game');
+  async listScenarios(): Promise<ScenarioSummary[]> {
+    return await call('list_scenarios');
+  },
+
+  async startNewGame(scenarioId?: string, character?: CharacterSheet): Promise<string> {
+    return await call('start_new_game', { scenarioId, character });
+  },
+
+  async loadGame(saveId: string): Promise<string> {
+    return await call('load_game', { saveId });
   },
 
   async getTurn(sessionId: string, turnNumber: number): Promise<TurnData> {
-    return await invoke('get_turn', { sessionId, turnNumber });
+    return await call('get_turn', { sessionId, turnNumber });
   },
 
   async submitAction(sessionId: string, action: string): Promise<TurnData> {
-    return await invoke('submit_action', { sessionId, action });
+    return await call('submit_action', { sessionId, action });
   },
 
   async submitActionStream(sessionId: string, action: string): Promise<void> {
-    return await invoke('submit_action_stream', { sessionId, action });
+    return await call('submit_action_stream', { sessionId, action });
+  },
+
+  async previewPrompt(sessionId: string, action: string): Promise<ChatMessage[]> {
+    return await call('preview_prompt', { sessionId, action });
+  },
+
+  async getWorldMap(sessionId: string): Promise<WorldMap> {
+    return await call('get_world_map', { sessionId });
+  },
+
+  async getSessionStats(sessionId: string): Promise<SessionStats> {
+    return await call('get_session_stats', { sessionId });
   },
 
   async listSaves(): Promise<SaveGame[]> {
-    return await invoke('list_saves');
+    return await call('list_saves');
   },
 
   async getOllamaConfig(): Promise<OllamaConfig> {
-    return await invoke('get_ollama_config');
+    return await call('get_ollama_config');
+  },
+
+  // `adminToken` is only needed with luup-server
+  async setOllamaConfig(ipAddress: string, adminToken?: string): Promise<void> {
+    return await call('set_ollama_config', usesServer ? { ipAddress, adminToken } : { ipAddress });
   },
 
-  async setOllamaConfig(ipAddress: string): Promise<void> {
-    return await invoke('set_ollama_config', { ipAddress });
+  // Subscribe to a session's stream messages; resolves to an unsubscribe function
+  async onAgentStream(sessionId: string, handler: (message: AgentMessage) => void): Promise<() => void> {
+    if (transport === 'tauri') {
+      return await listen<AgentMessage>('agent-stream', (event) => handler(event.payload));
+    }
+    return streamOverWebSocket(sessionId, handler);
   },
 };
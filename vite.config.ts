@@ -9,6 +9,10 @@ export default defineConfig({
   server: {
     port: 5173,
     strictPort: true,
+    // In a browser, API calls and turn streams go to luup-server
+    proxy: {
+      '/api': { target: 'http://localhost:8787', ws: true },
+    },
   },
   
   // Tauri uses a different pathname prefix in production